@@ -3,8 +3,23 @@ use textmode::Textmode as _;
 #[cfg(feature = "async")]
 #[tokio::main]
 async fn main() -> textmode::Result<()> {
-    let mut input = textmode::Input::new().await?;
-    let mut tm = textmode::Output::new().await?;
+    let (mut input, mut tm) = if std::env::args().any(|arg| arg == "--tty") {
+        (
+            textmode::Input::from_tty().await?,
+            textmode::Output::from_tty().await?,
+        )
+    } else {
+        (
+            textmode::Input::new().await?,
+            textmode::Output::new().await?,
+        )
+    };
+    if std::env::args().any(|arg| arg == "--auto-resize") {
+        tm.auto_resize(true)?;
+    }
+    if let Some(depth) = color_depth() {
+        tm.color_depth(depth);
+    }
     tm.move_to(5, 5);
     tm.write_str("foo");
     input.read_key().await?;
@@ -12,7 +27,7 @@ async fn main() -> textmode::Result<()> {
     input.read_key().await?;
 
     tm.move_to(8, 8);
-    tm.set_fgcolor(textmode::color::GREEN);
+    tm.set_fgcolor(fgcolor());
     tm.write_str("bar");
     tm.move_relative(3, 0);
     tm.set_fgcolor(textmode::Color::Default);
@@ -25,8 +40,23 @@ async fn main() -> textmode::Result<()> {
 
 #[cfg(not(feature = "async"))]
 fn main() {
-    let mut input = textmode::blocking::Input::new().unwrap();
-    let mut tm = textmode::blocking::Output::new().unwrap();
+    let (mut input, mut tm) = if std::env::args().any(|arg| arg == "--tty") {
+        (
+            textmode::blocking::Input::from_tty().unwrap(),
+            textmode::blocking::Output::from_tty().unwrap(),
+        )
+    } else {
+        (
+            textmode::blocking::Input::new().unwrap(),
+            textmode::blocking::Output::new().unwrap(),
+        )
+    };
+    if std::env::args().any(|arg| arg == "--auto-resize") {
+        tm.auto_resize(true).unwrap();
+    }
+    if let Some(depth) = color_depth() {
+        tm.color_depth(depth);
+    }
 
     tm.move_to(5, 5);
     tm.write_str("foo");
@@ -35,7 +65,7 @@ fn main() {
     input.read_key().unwrap();
 
     tm.move_to(8, 8);
-    tm.set_fgcolor(textmode::color::GREEN);
+    tm.set_fgcolor(fgcolor());
     tm.write_str("bar");
     tm.move_relative(3, 0);
     tm.set_fgcolor(textmode::Color::Default);
@@ -44,3 +74,23 @@ fn main() {
     tm.refresh().unwrap();
     input.read_key().unwrap();
 }
+
+fn color_depth() -> Option<textmode::ColorDepth> {
+    std::env::args().find_map(|arg| {
+        match arg.strip_prefix("--color-depth=")? {
+            "mono" => Some(textmode::ColorDepth::Monochrome),
+            "16" => Some(textmode::ColorDepth::Color16),
+            "256" => Some(textmode::ColorDepth::Color256),
+            "truecolor" => Some(textmode::ColorDepth::TrueColor),
+            _ => None,
+        }
+    })
+}
+
+fn fgcolor() -> textmode::Color {
+    if std::env::args().any(|arg| arg == "--rgb") {
+        textmode::Color::Rgb(0, 192, 0)
+    } else {
+        textmode::color::GREEN
+    }
+}
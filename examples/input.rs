@@ -1,18 +1,126 @@
+#[cfg(feature = "async")]
+use futures::stream::StreamExt as _;
+
 #[cfg(feature = "async")]
 #[tokio::main]
 async fn main() {
-    let mut input = textmode::Input::new().await.unwrap();
+    let mut input = if std::env::args().any(|arg| arg == "--tty") {
+        textmode::Input::from_tty().await.unwrap()
+    } else {
+        textmode::Input::new().await.unwrap()
+    };
+    // used to send queries to the terminal
+    let mut output = if std::env::args().any(|arg| arg == "--tty") {
+        textmode::Output::from_writer(
+            tokio::fs::OpenOptions::new()
+                .write(true)
+                .open("/dev/tty")
+                .await
+                .unwrap(),
+        )
+    } else {
+        textmode::Output::new_without_screen()
+    };
+    let mut screen = None;
+    let mut keymap = None;
+    let mut stream = false;
+    let mut shutdown = false;
     for arg in std::env::args().skip(1) {
         match arg.as_str() {
+            "--tty" => {}
+            "--stream" => stream = true,
+            "--shutdown" => shutdown = true,
             "--disable-utf8" => input.parse_utf8(false),
             "--disable-ctrl" => input.parse_ctrl(false),
             "--disable-meta" => input.parse_meta(false),
             "--disable-special-keys" => input.parse_special_keys(false),
             "--disable-single" => input.parse_single(false),
+            "--keymap" => keymap = Some(make_keymap()),
+            "--escape-timeout" => {
+                input.escape_timeout(std::time::Duration::from_millis(500))
+            }
+            "--terminfo-linux" => input.terminfo_keys(Some(
+                textmode::TerminfoKeys::load("linux").unwrap(),
+            )),
+            "--enable-kitty-keyboard" => input
+                .enable_kitty_keyboard(
+                    textmode::KittyFlags::DISAMBIGUATE_ESCAPE_CODES
+                        | textmode::KittyFlags::REPORT_EVENT_TYPES,
+                )
+                .await
+                .unwrap(),
+            "--enable-mouse" => {
+                input.enable_mouse(textmode::MouseMode::Drag).await.unwrap()
+            }
+            "--enable-focus-reporting" => {
+                input.enable_focus_reporting().await.unwrap()
+            }
+            "--query-cursor-position" => print!(
+                "{:?}\r\n",
+                input
+                    .query_cursor_position(
+                        &mut output,
+                        std::time::Duration::from_secs(1),
+                    )
+                    .await
+                    .unwrap()
+            ),
+            "--query-device-status" => print!(
+                "{:?}\r\n",
+                input
+                    .query_device_status(
+                        &mut output,
+                        std::time::Duration::from_secs(1),
+                    )
+                    .await
+                    .unwrap()
+            ),
+            "--enable-bracketed-paste" => {
+                let mut guard = textmode::ScreenGuard::new().await.unwrap();
+                guard.enable_bracketed_paste().await.unwrap();
+                screen = Some(guard);
+            }
             _ => panic!("unknown arg {}", arg),
         }
     }
 
+    if let Some(mut keymap) = keymap {
+        while let Some(event) = input.read_binding(&mut keymap).await.unwrap()
+        {
+            print!("{:?}\r\n", event);
+            if event
+                == textmode::KeymapEvent::Unmatched(vec![
+                    textmode::Key::Ctrl(b'c'),
+                ])
+            {
+                break;
+            }
+        }
+        return;
+    }
+
+    if shutdown {
+        // a pending read shouldn't keep the runtime from shutting down
+        tokio::spawn(async move { input.read_key().await });
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        print!("done\r\n");
+        return;
+    }
+
+    if stream {
+        let mut stream = input.into_stream();
+        while let Some(key) = stream.next().await {
+            let key = key.unwrap();
+            print!("{:?}: ", key);
+            let bytes = key.into_bytes();
+            print!("{:?}\r\n", bytes);
+            if bytes.contains(&3) {
+                break;
+            }
+        }
+        return;
+    }
+
     loop {
         let key = input.read_key().await.unwrap();
         if let Some(key) = key {
@@ -26,22 +134,126 @@ async fn main() {
             break;
         }
     }
+
+    drop(screen);
 }
 
 #[cfg(not(feature = "async"))]
 fn main() {
-    let mut input = textmode::blocking::Input::new().unwrap();
+    let mut input = if std::env::args().any(|arg| arg == "--tty") {
+        textmode::blocking::Input::from_tty().unwrap()
+    } else {
+        textmode::blocking::Input::new().unwrap()
+    };
+    // used to send queries to the terminal
+    let mut output = if std::env::args().any(|arg| arg == "--tty") {
+        textmode::blocking::Output::from_writer(
+            std::fs::OpenOptions::new()
+                .write(true)
+                .open("/dev/tty")
+                .unwrap(),
+        )
+    } else {
+        textmode::blocking::Output::new_without_screen()
+    };
+    let mut screen = None;
+    let mut keymap = None;
+    let mut read_timeout = false;
     for arg in std::env::args().skip(1) {
         match arg.as_str() {
+            "--tty" => {}
+            "--read-timeout" => read_timeout = true,
             "--disable-utf8" => input.parse_utf8(false),
             "--disable-ctrl" => input.parse_ctrl(false),
             "--disable-meta" => input.parse_meta(false),
             "--disable-special-keys" => input.parse_special_keys(false),
             "--disable-single" => input.parse_single(false),
+            "--keymap" => keymap = Some(make_keymap()),
+            "--escape-timeout" => {
+                input.escape_timeout(std::time::Duration::from_millis(500))
+            }
+            "--terminfo-linux" => input.terminfo_keys(Some(
+                textmode::TerminfoKeys::load("linux").unwrap(),
+            )),
+            "--enable-kitty-keyboard" => input
+                .enable_kitty_keyboard(
+                    textmode::KittyFlags::DISAMBIGUATE_ESCAPE_CODES
+                        | textmode::KittyFlags::REPORT_EVENT_TYPES,
+                )
+                .unwrap(),
+            "--enable-mouse" => {
+                input.enable_mouse(textmode::MouseMode::Drag).unwrap()
+            }
+            "--enable-focus-reporting" => {
+                input.enable_focus_reporting().unwrap()
+            }
+            "--query-cursor-position" => print!(
+                "{:?}\r\n",
+                input
+                    .query_cursor_position(
+                        &mut output,
+                        std::time::Duration::from_secs(1),
+                    )
+                    .unwrap()
+            ),
+            "--query-device-status" => print!(
+                "{:?}\r\n",
+                input
+                    .query_device_status(
+                        &mut output,
+                        std::time::Duration::from_secs(1),
+                    )
+                    .unwrap()
+            ),
+            "--enable-bracketed-paste" => {
+                let mut guard =
+                    textmode::blocking::ScreenGuard::new().unwrap();
+                guard.enable_bracketed_paste().unwrap();
+                screen = Some(guard);
+            }
             _ => panic!("unknown arg {}", arg),
         }
     }
 
+    if let Some(mut keymap) = keymap {
+        while let Some(event) = input.read_binding(&mut keymap).unwrap() {
+            print!("{:?}\r\n", event);
+            if event
+                == textmode::KeymapEvent::Unmatched(vec![
+                    textmode::Key::Ctrl(b'c'),
+                ])
+            {
+                break;
+            }
+        }
+        return;
+    }
+
+    if read_timeout {
+        // only report the first of a run of timeouts
+        let mut timed_out = false;
+        loop {
+            let key = input
+                .read_key_timeout(std::time::Duration::from_millis(100))
+                .unwrap();
+            if let Some(key) = key {
+                timed_out = false;
+                print!("{:?}: ", key);
+                let bytes = key.into_bytes();
+                print!("{:?}\r\n", bytes);
+                if bytes.contains(&3) {
+                    break;
+                }
+            } else if input.is_eof() {
+                break;
+            } else if !timed_out {
+                timed_out = true;
+                print!("timeout\r\n");
+            }
+        }
+        return;
+    }
+
     loop {
         let key = input.read_key().unwrap();
         if let Some(key) = key {
@@ -55,4 +267,22 @@ fn main() {
             break;
         }
     }
+
+    drop(screen);
+}
+
+fn make_keymap() -> textmode::Keymap<&'static str> {
+    let mut keymap = textmode::Keymap::new();
+    keymap.bind(
+        [textmode::Key::Ctrl(b'x'), textmode::Key::Ctrl(b's')],
+        "save",
+    );
+    keymap.bind(
+        [textmode::Key::Ctrl(b'x'), textmode::Key::Char('k')],
+        "kill",
+    );
+    keymap.bind([textmode::Key::Char('g'), textmode::Key::Char('g')], "top");
+    keymap.bind([textmode::Key::Char('g')], "g");
+    keymap.timeout(Some(std::time::Duration::from_millis(500)));
+    keymap
 }
@@ -1,22 +1,24 @@
 use std::os::fd::AsFd as _;
 
 use futures::stream::StreamExt as _;
+use textmode::Draw as _;
 use textmode::Textmode as _;
 use tokio::io::AsyncWriteExt as _;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Command {
     NewWindow,
     NextWindow,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Event {
     Input(textmode::Key),
     Output,
     WindowExit(usize),
     Command(Command),
     Notification,
+    Resize(u16, u16),
 }
 
 struct Window {
@@ -32,6 +34,7 @@ struct Notification {
 }
 
 struct State {
+    size: (u16, u16),
     windows: std::collections::BTreeMap<usize, Window>,
     current_window: usize,
     next_window_id: usize,
@@ -42,9 +45,10 @@ struct State {
 }
 
 impl State {
-    fn new() -> Self {
+    fn new(size: (u16, u16)) -> Self {
         let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
         Self {
+            size,
             windows: std::collections::BTreeMap::new(),
             current_window: 0,
             next_window_id: 0,
@@ -94,47 +98,40 @@ impl State {
     fn spawn_input_thread(&self, mut input: textmode::blocking::Input) {
         let notify = self.wevents.clone();
         std::thread::spawn(move || {
-            let mut waiting_for_command = false;
+            let mut keymap = textmode::Keymap::new();
+            keymap.bind(
+                [textmode::Key::Ctrl(b'n'), textmode::Key::Ctrl(b'n')],
+                Event::Input(textmode::Key::Ctrl(b'n')),
+            );
+            keymap.bind(
+                [textmode::Key::Ctrl(b'n'), textmode::Key::Byte(b'c')],
+                Event::Command(Command::NewWindow),
+            );
+            keymap.bind(
+                [textmode::Key::Ctrl(b'n'), textmode::Key::Byte(b'n')],
+                Event::Command(Command::NextWindow),
+            );
             input.parse_utf8(false);
             input.parse_meta(false);
             input.parse_special_keys(false);
             loop {
-                input.parse_single(waiting_for_command);
+                input.parse_single(keymap.is_pending());
                 match input.read_key() {
-                    Ok(Some(key)) => {
-                        if waiting_for_command {
-                            waiting_for_command = false;
-                            match key {
-                                textmode::Key::Ctrl(b'n') => {
-                                    notify.send(Event::Input(key)).unwrap();
-                                }
-                                textmode::Key::Byte(b'c') => {
-                                    notify
-                                        .send(Event::Command(
-                                            Command::NewWindow,
-                                        ))
-                                        .unwrap();
-                                }
-                                textmode::Key::Byte(b'n') => {
-                                    notify
-                                        .send(Event::Command(
-                                            Command::NextWindow,
-                                        ))
-                                        .unwrap();
-                                }
-                                _ => {} // ignore
-                            }
-                        } else {
-                            match key {
-                                textmode::Key::Ctrl(b'n') => {
-                                    waiting_for_command = true;
-                                }
-                                _ => {
-                                    notify.send(Event::Input(key)).unwrap();
-                                }
+                    Ok(Some(key)) => match keymap.feed(key) {
+                        textmode::KeymapEvent::Action(event) => {
+                            notify.send(event).unwrap();
+                        }
+                        textmode::KeymapEvent::Pending => {}
+                        textmode::KeymapEvent::Unmatched(mut keys) => {
+                            // unknown commands after the prefix key are
+                            // ignored
+                            if keys.len() == 1 {
+                                notify
+                                    .send(Event::Input(keys.remove(0)))
+                                    .unwrap();
                             }
                         }
-                    }
+                    },
                     Ok(None) => {
                         break;
                     }
@@ -147,17 +144,43 @@ impl State {
         });
     }
 
+    fn spawn_resize_task(&self) {
+        let notify = self.wevents.clone();
+        let mut watcher = textmode::ResizeWatcher::new().unwrap();
+        tokio::task::spawn(async move {
+            while let Some((rows, cols)) = watcher.recv().await {
+                if notify.send(Event::Resize(rows, cols)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    async fn resize(&mut self, rows: u16, cols: u16) {
+        self.size = (rows, cols);
+        for window in self.windows.values_mut() {
+            window
+                .pty_w
+                .resize(pty_process::Size::new(rows, cols))
+                .unwrap();
+            let mut vt = window.vt.clone().lock_owned().await;
+            vt.set_size(rows, cols);
+            window.screen = vt.screen().clone();
+        }
+    }
+
     fn new_window(
         &mut self,
         notify: tokio::sync::mpsc::UnboundedSender<Event>,
     ) {
         let (pty, pts) = pty_process::open().unwrap();
         let pts_clone = pts.as_fd().try_clone_to_owned().unwrap();
-        pty.resize(pty_process::Size::new(24, 80)).unwrap();
+        let (rows, cols) = self.size;
+        pty.resize(pty_process::Size::new(rows, cols)).unwrap();
         let cmd = pty_process::Command::new("zsh");
         let mut child = cmd.spawn(pts).unwrap();
         let (pty_r, pty_w) = pty.into_split();
-        let vt = vt100::Parser::default();
+        let vt = vt100::Parser::new(rows, cols, 0);
         let screen = vt.screen().clone();
         let vt = std::sync::Arc::new(tokio::sync::Mutex::new(vt));
         let id = self.next_window_id;
@@ -247,7 +270,7 @@ impl State {
         let reset_attrs = screen.attributes_formatted();
         let pos = screen.cursor_position();
         for (i, row) in screen
-            .rows_formatted(0, 80)
+            .rows_formatted(0, self.size.1)
             .enumerate()
             .take(self.notifications.len())
         {
@@ -289,11 +312,11 @@ impl State {
         for (i, notification) in self.notifications.values().enumerate() {
             tm.move_to(i as u16, 0);
             tm.clear_line();
-            let str_len = notification.text.len();
-            let spaces = 80 - str_len;
-            let prefix_spaces = spaces / 2;
-            tm.write(&vec![b' '; prefix_spaces]);
-            tm.write_str(&notification.text);
+            tm.write_aligned(
+                &notification.text,
+                usize::from(self.size.1),
+                textmode::text::Align::Center,
+            );
         }
         tm.move_to(pos.0, pos.1);
         tm.write(&reset_attrs);
@@ -311,7 +334,7 @@ impl Tmux {
     pub async fn new() -> Self {
         let input = textmode::blocking::Input::new().unwrap();
         let tm = textmode::Output::new().await.unwrap();
-        let state = State::new();
+        let state = State::new(tm.screen().size());
         Self { input, tm, state }
     }
 
@@ -324,6 +347,7 @@ impl Tmux {
 
         let _raw_guard = input.take_raw_guard();
         state.spawn_input_thread(input);
+        state.spawn_resize_task();
 
         state.new_window(state.wevents.clone());
 
@@ -367,6 +391,12 @@ impl Tmux {
                 Some(Event::Notification) => {
                     state.update_current_window(&mut tm).await;
                 }
+                Some(Event::Resize(rows, cols)) => {
+                    state.resize(rows, cols).await;
+                    tm.set_size(rows, cols);
+                    state.redraw_current_window(&mut tm).await;
+                    tm.hard_refresh().await.unwrap();
+                }
                 None => {
                     break;
                 }
@@ -2,10 +2,15 @@ use std::io::Read as _;
 
 use crate::private::Input as _;
 
-/// Switches the terminal on `stdin` to raw mode, and restores it when this
-/// object goes out of scope.
+/// Switches the terminal on `stdin` (or another terminal) to raw mode, and
+/// restores it when this object goes out of scope.
 pub struct RawGuard {
+    // None means stdin, with escape sequences written to stdout
+    tty: Option<std::fs::File>,
     termios: Option<rustix::termios::Termios>,
+    kitty_keyboard: bool,
+    mouse: Option<crate::MouseMode>,
+    focus_reporting: bool,
 }
 
 impl RawGuard {
@@ -16,38 +21,142 @@ impl RawGuard {
     /// # Errors
     /// * `Error::SetTerminalMode`: failed to put the terminal into raw mode
     pub fn new() -> crate::error::Result<Self> {
-        let stdin = std::io::stdin();
-        let termios = rustix::termios::tcgetattr(&stdin)
-            .map_err(crate::error::Error::SetTerminalMode)?;
-        let mut termios_raw = termios.clone();
-        termios_raw.make_raw();
-        rustix::termios::tcsetattr(
-            &stdin,
-            rustix::termios::OptionalActions::Now,
-            &termios_raw,
-        )
-        .map_err(crate::error::Error::SetTerminalMode)?;
+        Self::new_for_tty(None)
+    }
+
+    /// Switches the given terminal to raw mode and returns a guard object.
+    /// Escape sequences written by this guard (for instance, by
+    /// [`enable_mouse`](RawGuard::enable_mouse)) are written to this
+    /// terminal rather than to `stdout`. This is typically called as part of
+    /// [`Input::from_fd`](Input::from_fd).
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to duplicate the file descriptor
+    /// * `Error::SetTerminalMode`: failed to put the terminal into raw mode
+    pub fn from_fd(fd: impl std::os::fd::AsFd) -> crate::error::Result<Self> {
+        let tty = fd
+            .as_fd()
+            .try_clone_to_owned()
+            .map_err(crate::error::Error::OpenTerminal)?;
+        Self::new_for_tty(Some(tty.into()))
+    }
+
+    fn new_for_tty(tty: Option<std::fs::File>) -> crate::error::Result<Self> {
+        let termios = match &tty {
+            Some(tty) => make_raw(tty)?,
+            None => make_raw(std::io::stdin())?,
+        };
         Ok(Self {
+            tty,
             termios: Some(termios),
+            kitty_keyboard: false,
+            mouse: None,
+            focus_reporting: false,
         })
     }
 
+    /// Enables the kitty keyboard protocol with the given flags, by writing
+    /// the appropriate escape sequence to the terminal. The protocol will be
+    /// disabled again when this guard is cleaned up. Calling this again
+    /// replaces the previously enabled flags.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub fn enable_kitty_keyboard(
+        &mut self,
+        flags: crate::KittyFlags,
+    ) -> crate::error::Result<()> {
+        let mut buf = vec![];
+        if self.kitty_keyboard {
+            buf.extend(crate::KITTY_KEYBOARD_POP);
+        }
+        buf.extend(flags.push_sequence());
+        self.write(&buf)?;
+        self.kitty_keyboard = true;
+        Ok(())
+    }
+
+    /// Enables mouse reporting in the given mode, by writing the
+    /// appropriate escape sequence to the terminal. Mouse events are
+    /// reported using SGR encoding. Mouse reporting will be disabled again
+    /// when this guard is cleaned up. Calling this again replaces the
+    /// previously enabled mode.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub fn enable_mouse(
+        &mut self,
+        mode: crate::MouseMode,
+    ) -> crate::error::Result<()> {
+        let mut buf = vec![];
+        if let Some(mode) = self.mouse {
+            buf.extend(mode.disable_sequence());
+        }
+        buf.extend(mode.enable_sequence());
+        self.write(&buf)?;
+        self.mouse = Some(mode);
+        Ok(())
+    }
+
+    /// Enables focus reporting, by writing the appropriate escape sequence
+    /// to the terminal. Focus reporting will be disabled again when this
+    /// guard is cleaned up.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub fn enable_focus_reporting(&mut self) -> crate::error::Result<()> {
+        if !self.focus_reporting {
+            self.write(crate::FOCUS_REPORTING_ENABLE)?;
+            self.focus_reporting = true;
+        }
+        Ok(())
+    }
+
     /// Switch back from raw mode early.
     ///
     /// # Errors
+    /// * `Error::WriteStdout`: failed to disable the kitty keyboard
+    ///   protocol, mouse reporting, or focus reporting
     /// * `Error::SetTerminalMode`: failed to return the terminal from raw
     ///   mode
     pub fn cleanup(&mut self) -> crate::error::Result<()> {
+        let deinit = self.deinit_sequence();
+        if !deinit.is_empty() {
+            self.write(&deinit)?;
+        }
         self.termios.take().map_or(Ok(()), |termios| {
-            let stdin = std::io::stdin();
-            rustix::termios::tcsetattr(
-                &stdin,
-                rustix::termios::OptionalActions::Now,
-                &termios,
+            self.tty.as_ref().map_or_else(
+                || restore(std::io::stdin(), &termios),
+                |tty| restore(tty, &termios),
             )
-            .map_err(crate::error::Error::SetTerminalMode)
         })
     }
+
+    fn write(&self, buf: &[u8]) -> crate::error::Result<()> {
+        self.tty.as_ref().map_or_else(
+            || super::output::write_stdout(buf),
+            |tty| super::output::write_tty(tty, buf),
+        )
+    }
+
+    fn deinit_sequence(&mut self) -> Vec<u8> {
+        let mut buf = vec![];
+        if let Some(mode) = self.mouse.take() {
+            buf.extend(mode.disable_sequence());
+        }
+        if self.focus_reporting {
+            self.focus_reporting = false;
+            buf.extend(crate::FOCUS_REPORTING_DISABLE);
+        }
+        if self.kitty_keyboard {
+            self.kitty_keyboard = false;
+            buf.extend(crate::KITTY_KEYBOARD_POP);
+        }
+        buf
+    }
 }
 
 impl Drop for RawGuard {
@@ -57,13 +166,15 @@ impl Drop for RawGuard {
     }
 }
 
-/// Manages handling terminal input from `stdin`.
+/// Manages handling terminal input from `stdin` (or another terminal or
+/// reader).
 ///
 /// The primary interface provided is [`read_key`](Input::read_key). You can
 /// additionally configure the types of keypresses you are interested in
 /// through the `parse_*` methods. This configuration can be changed between
 /// any two calls to [`read_key`](Input::read_key).
 pub struct Input {
+    source: Source,
     raw: Option<RawGuard>,
 
     buf: Vec<u8>,
@@ -74,6 +185,10 @@ pub struct Input {
     parse_meta: bool,
     parse_special_keys: bool,
     parse_single: bool,
+    escape_timeout: std::time::Duration,
+    escape_deadline: Option<std::time::Instant>,
+    terminfo_keys: Option<crate::TerminfoKeys>,
+    eof: bool,
 }
 
 impl crate::private::Input for Input {
@@ -120,6 +235,10 @@ impl crate::private::Input for Input {
     fn should_parse_special_keys(&self) -> bool {
         self.parse_special_keys
     }
+
+    fn terminfo_keys(&self) -> Option<&crate::TerminfoKeys> {
+        self.terminfo_keys.as_ref()
+    }
 }
 
 impl Input {
@@ -134,11 +253,63 @@ impl Input {
         Ok(self_)
     }
 
+    /// Creates a new `Input` instance which reads from the given terminal
+    /// instead of `stdin`, containing a [`RawGuard`](RawGuard) instance for
+    /// that terminal. Queries such as
+    /// [`query_cursor_position`](Input::query_cursor_position) are also
+    /// written to this terminal rather than to `stdout`.
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to duplicate the file descriptor
+    /// * `Error::SetTerminalMode`: failed to put the terminal into raw mode
+    pub fn from_fd(fd: impl std::os::fd::AsFd) -> crate::error::Result<Self> {
+        let tty: std::fs::File = fd
+            .as_fd()
+            .try_clone_to_owned()
+            .map_err(crate::error::Error::OpenTerminal)?
+            .into();
+        let raw = RawGuard::from_fd(&tty)?;
+        let mut self_ = Self::new_from_source(Source::Tty(tty));
+        self_.raw = Some(raw);
+        Ok(self_)
+    }
+
+    /// Creates a new `Input` instance which reads from the controlling
+    /// terminal (`/dev/tty`) instead of `stdin`. This allows reading
+    /// keypresses even when `stdin` is redirected, for instance when data
+    /// is being piped into the program. See [`from_fd`](Input::from_fd).
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to open `/dev/tty`
+    /// * `Error::SetTerminalMode`: failed to put the terminal into raw mode
+    pub fn from_tty() -> crate::error::Result<Self> {
+        Self::from_fd(super::output::open_tty()?)
+    }
+
+    /// Creates a new `Input` instance which parses keypresses from the data
+    /// produced by the given reader, without creating a
+    /// [`RawGuard`](RawGuard) instance. Since arbitrary readers can't be
+    /// polled, timeouts (such as the one set by
+    /// [`escape_timeout`](Input::escape_timeout)) will block on the reader
+    /// rather than expiring, and
+    /// [`query_cursor_position`](Input::query_cursor_position) and
+    /// [`query_device_status`](Input::query_device_status) will return an
+    /// error.
+    #[must_use]
+    pub fn from_reader(reader: impl std::io::Read + Send + 'static) -> Self {
+        Self::new_from_source(Source::Reader(Box::new(reader)))
+    }
+
     /// Creates a new `Input` instance without creating a
     /// [`RawGuard`](RawGuard) instance.
     #[must_use]
     pub fn new_without_raw() -> Self {
+        Self::new_from_source(Source::Stdin)
+    }
+
+    fn new_from_source(source: Source) -> Self {
         Self {
+            source,
             raw: None,
             buf: Vec::with_capacity(4096),
             pos: 0,
@@ -147,6 +318,10 @@ impl Input {
             parse_meta: true,
             parse_special_keys: true,
             parse_single: true,
+            escape_timeout: std::time::Duration::ZERO,
+            escape_deadline: None,
+            terminfo_keys: None,
+            eof: false,
         }
     }
 
@@ -157,6 +332,63 @@ impl Input {
         self.raw.take()
     }
 
+    /// Enables the kitty keyboard protocol with the given flags, which
+    /// allows `read_key` to report keys that would otherwise be ambiguous
+    /// (as well as key releases and repeats, if requested) as
+    /// [`Event`](crate::Key::Event) keys. This is done through the
+    /// [`RawGuard`](RawGuard) instance stored in this `Input` instance, and
+    /// so does nothing if there isn't one; in that case, call
+    /// [`RawGuard::enable_kitty_keyboard`] directly instead.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub fn enable_kitty_keyboard(
+        &mut self,
+        flags: crate::KittyFlags,
+    ) -> crate::error::Result<()> {
+        if let Some(raw) = &mut self.raw {
+            raw.enable_kitty_keyboard(flags)?;
+        }
+        Ok(())
+    }
+
+    /// Enables mouse reporting in the given mode, which allows `read_key` to
+    /// report [`Mouse`](crate::Key::Mouse) keys. This is done through the
+    /// [`RawGuard`](RawGuard) instance stored in this `Input` instance, and
+    /// so does nothing if there isn't one; in that case, call
+    /// [`RawGuard::enable_mouse`] directly instead.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub fn enable_mouse(
+        &mut self,
+        mode: crate::MouseMode,
+    ) -> crate::error::Result<()> {
+        if let Some(raw) = &mut self.raw {
+            raw.enable_mouse(mode)?;
+        }
+        Ok(())
+    }
+
+    /// Enables focus reporting, which allows `read_key` to report
+    /// [`FocusGained`](crate::Key::FocusGained) and
+    /// [`FocusLost`](crate::Key::FocusLost) keys. This is done through the
+    /// [`RawGuard`](RawGuard) instance stored in this `Input` instance, and
+    /// so does nothing if there isn't one; in that case, call
+    /// [`RawGuard::enable_focus_reporting`] directly instead.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub fn enable_focus_reporting(&mut self) -> crate::error::Result<()> {
+        if let Some(raw) = &mut self.raw {
+            raw.enable_focus_reporting()?;
+        }
+        Ok(())
+    }
+
     /// Sets whether `read_key` should try to produce
     /// [`String`](crate::Key::String) or [`Char`](crate::Key::Char) keys when
     /// possible, rather than [`Bytes`](crate::Key::Bytes) or
@@ -203,67 +435,407 @@ impl Input {
         self.parse_single = parse;
     }
 
+    /// Sets how long `read_key` should wait for the rest of an escape
+    /// sequence to arrive after receiving a partial one (for instance, a lone
+    /// `ESC` byte). If no more bytes arrive within this time, the bytes that
+    /// were received will be parsed on their own (so a lone `ESC` byte will
+    /// produce [`Escape`](crate::Key::Escape)). This is similar to the
+    /// `ESCDELAY` setting in ncurses, and can be useful over slow
+    /// connections where escape sequences may be split across multiple
+    /// reads. Defaults to zero, meaning to not wait at all.
+    pub fn escape_timeout(&mut self, timeout: std::time::Duration) {
+        self.escape_timeout = timeout;
+    }
+
+    /// Sets a table of additional key sequences for `read_key` to
+    /// recognize, typically loaded from the terminfo entry for the current
+    /// terminal with [`TerminfoKeys::from_env`](crate::TerminfoKeys::from_env).
+    /// Sequences in this table take precedence over the built-in parsing,
+    /// which is still used for any sequences not in the table. Only has an
+    /// effect when `parse_special_keys` is enabled. Defaults to `None`.
+    pub fn terminfo_keys(&mut self, keys: Option<crate::TerminfoKeys>) {
+        self.terminfo_keys = keys;
+    }
+
+    /// Asks the terminal for the current position of the cursor, by
+    /// writing `ESC[6n` to the terminal that `output` draws to and waiting
+    /// up to `timeout` for the reply to arrive on this input's terminal.
+    /// Returns the position as `(row, col)` (zero-based, as used by
+    /// [`move_to`](crate::Textmode::move_to)), or `None` if no reply arrived
+    /// in time. Any other input received while waiting is kept, and will be
+    /// returned by later calls to `read_key`.
+    ///
+    /// Note that the reply uses the same format as F3 pressed with
+    /// modifiers (`ESC[1;2R` is both shift+F3 and a reply of row 1, column
+    /// 2), so such a keypress received while waiting will be treated as the
+    /// reply.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the query to the terminal
+    /// * `Error::ReadStdin`: failed to read data from stdin, or this input
+    ///   was created with [`from_reader`](Input::from_reader) (which can't
+    ///   be waited on with a timeout)
+    pub fn query_cursor_position(
+        &mut self,
+        output: &mut super::Output,
+        timeout: std::time::Duration,
+    ) -> crate::error::Result<Option<(u16, u16)>> {
+        let Some(params) =
+            self.query(output, b"\x1b[6n", b'R', 2, timeout)?
+        else {
+            return Ok(None);
+        };
+        let row =
+            u16::try_from(params[0].saturating_sub(1)).unwrap_or(u16::MAX);
+        let col =
+            u16::try_from(params[1].saturating_sub(1)).unwrap_or(u16::MAX);
+        Ok(Some((row, col)))
+    }
+
+    /// Asks the terminal for its status, by writing `ESC[5n` to the
+    /// terminal that `output` draws to and waiting up to `timeout` for the
+    /// reply to arrive on this input's terminal. Returns `Some(true)` if the
+    /// terminal reports that it is working correctly, `Some(false)` if it
+    /// reports a malfunction, or `None` if no reply arrived in time. Any
+    /// other input received while waiting is kept, and will be returned by
+    /// later calls to `read_key`.
+    ///
+    /// Since every terminal replies to this query, this can also be used
+    /// to check whether the terminal is responding at all.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the query to the terminal
+    /// * `Error::ReadStdin`: failed to read data from stdin, or this input
+    ///   was created with [`from_reader`](Input::from_reader) (which can't
+    ///   be waited on with a timeout)
+    pub fn query_device_status(
+        &mut self,
+        output: &mut super::Output,
+        timeout: std::time::Duration,
+    ) -> crate::error::Result<Option<bool>> {
+        Ok(self
+            .query(output, b"\x1b[5n", b'n', 1, timeout)?
+            .map(|params| params[0] == 0))
+    }
+
     /// Reads a keypress from the terminal on `stdin`. Returns `Ok(None)` on
     /// EOF.
     ///
     /// # Errors
     /// * `Error::ReadStdin`: failed to read data from stdin
     pub fn read_key(&mut self) -> crate::error::Result<Option<crate::Key>> {
-        self.fill_buf()?;
+        self.fill_buf(None)?;
+        Ok(self.parse_key())
+    }
+
+    /// Reads a keypress from the terminal on `stdin`, waiting at most
+    /// `timeout` for one to arrive. Returns `Ok(None)` if no complete
+    /// keypress was received before the timeout expired, or on EOF (which
+    /// can be distinguished with [`is_eof`](Input::is_eof)). Any partial
+    /// escape sequence or UTF-8 character received before the timeout
+    /// expired is kept, and will be completed by the next read. Note that
+    /// inputs created with [`from_reader`](Input::from_reader) can't be
+    /// polled, and so will block until data is available.
+    ///
+    /// # Errors
+    /// * `Error::ReadStdin`: failed to read data from stdin
+    pub fn read_key_timeout(
+        &mut self,
+        timeout: std::time::Duration,
+    ) -> crate::error::Result<Option<crate::Key>> {
+        let deadline = std::time::Instant::now().checked_add(timeout);
+        if !self.fill_buf(deadline)? {
+            return Ok(None);
+        }
+        Ok(self.parse_key())
+    }
+
+    /// Reads a keypress from the terminal on `stdin` if one is available
+    /// without waiting. This is equivalent to
+    /// [`read_key_timeout`](Input::read_key_timeout) with a timeout of
+    /// zero.
+    ///
+    /// # Errors
+    /// * `Error::ReadStdin`: failed to read data from stdin
+    pub fn try_read_key(
+        &mut self,
+    ) -> crate::error::Result<Option<crate::Key>> {
+        self.read_key_timeout(std::time::Duration::ZERO)
+    }
 
+    /// Returns whether the most recent read from `stdin` reached EOF. This
+    /// can be used to tell the difference between a timeout and EOF when
+    /// [`read_key_timeout`](Input::read_key_timeout) returns `Ok(None)`.
+    #[must_use]
+    pub fn is_eof(&self) -> bool {
+        self.eof && self.buf_is_empty()
+    }
+
+    /// Reads keypresses from the terminal on `stdin` until they match a
+    /// binding in `keymap` or fail to match any binding, and returns the
+    /// result (which will never be [`Pending`](crate::KeymapEvent::Pending)).
+    /// If the keymap has a timeout set, a partially entered sequence is
+    /// ended once no key has been received for that long. Returns
+    /// `Ok(None)` on EOF, after returning any keys which were still
+    /// pending.
+    ///
+    /// # Errors
+    /// * `Error::ReadStdin`: failed to read data from stdin
+    pub fn read_binding<T: Clone>(
+        &mut self,
+        keymap: &mut crate::Keymap<T>,
+    ) -> crate::error::Result<Option<crate::KeymapEvent<T>>> {
+        loop {
+            if let Some(event) = keymap.next_event() {
+                return Ok(Some(event));
+            }
+
+            if let Some(deadline) = keymap.deadline() {
+                let remaining = deadline
+                    .saturating_duration_since(std::time::Instant::now());
+                if self.buf_is_empty()
+                    && (remaining.is_zero()
+                        || !self.source.poll(remaining)?)
+                {
+                    if let Some(event) = keymap.flush() {
+                        return Ok(Some(event));
+                    }
+                }
+            }
+
+            let Some(key) = self.read_key()? else {
+                return Ok(keymap.flush());
+            };
+            match keymap.feed(key) {
+                crate::KeymapEvent::Pending => {}
+                event => return Ok(Some(event)),
+            }
+        }
+    }
+
+    fn parse_key(&mut self) -> Option<crate::Key> {
         if self.parse_single {
-            Ok(self.read_single_key())
+            self.read_single_key()
         } else {
             if let Some(key) = self.try_read_string() {
-                return Ok(Some(key));
+                return Some(key);
             }
 
             if let Some(key) = self.try_read_bytes() {
-                return Ok(Some(key));
-            }
-
-            if let Some(key) = self.read_single_key() {
-                return Ok(Some(self.normalize_to_bytes(key)));
+                return Some(key);
             }
 
-            Ok(None)
+            self.read_single_key()
+                .map(|key| self.normalize_to_bytes(key))
         }
     }
 
-    fn fill_buf(&mut self) -> crate::error::Result<()> {
+    // returns false if the deadline passed before a complete key was
+    // available (in which case the partial key is left in the buffer for
+    // the next call to pick up)
+    fn fill_buf(
+        &mut self,
+        deadline: Option<std::time::Instant>,
+    ) -> crate::error::Result<bool> {
         if self.buf_is_empty() {
-            self.buf.resize(4096, 0);
+            self.buf.clear();
             self.pos = 0;
-            let bytes = read_stdin(&mut self.buf)?;
+            let Some(bytes) = self.read_more(deadline)? else {
+                return Ok(false);
+            };
             if bytes == 0 {
-                return Ok(());
+                return Ok(true);
             }
-            self.buf.truncate(bytes);
         }
 
         if self.parse_utf8 {
             let expected_bytes =
                 self.expected_leading_utf8_bytes(self.buf()[0]);
-            if self.buf.len() < self.pos + expected_bytes {
-                let mut cur = self.buf.len();
-                self.buf.resize(4096 + expected_bytes, 0);
-                while cur < self.pos + expected_bytes {
-                    let bytes = read_stdin(&mut self.buf[cur..])?;
-                    if bytes == 0 {
-                        return Ok(());
+            while self.buf.len() < self.pos + expected_bytes {
+                let Some(bytes) = self.read_more(deadline)? else {
+                    return Ok(false);
+                };
+                if bytes == 0 {
+                    return Ok(true);
+                }
+            }
+        }
+
+        if !self.escape_timeout.is_zero() {
+            while self.escape_sequence_is_incomplete() {
+                // the escape timeout is measured from when the partial
+                // sequence first arrived, even if we have returned in
+                // between due to the deadline
+                let escape_deadline =
+                    *self.escape_deadline.get_or_insert_with(|| {
+                        std::time::Instant::now() + self.escape_timeout
+                    });
+                let wait_until = deadline
+                    .map_or(escape_deadline, |d| d.min(escape_deadline));
+                let Some(bytes) = self.read_more(Some(wait_until))? else {
+                    if wait_until < escape_deadline {
+                        return Ok(false);
                     }
-                    cur += bytes;
+                    break;
+                };
+                if bytes == 0 {
+                    break;
                 }
-                self.buf.truncate(cur);
             }
+            self.escape_deadline = None;
         }
 
-        Ok(())
+        while self.paste_is_incomplete() {
+            let Some(bytes) = self.read_more(deadline)? else {
+                return Ok(false);
+            };
+            if bytes == 0 {
+                return Ok(true);
+            }
+        }
+
+        Ok(true)
+    }
+
+    // appends more data from the source to the buffer, waiting until the
+    // deadline (or forever, if there isn't one) for it to become available.
+    // returns None if the deadline passed without any data arriving.
+    fn read_more(
+        &mut self,
+        deadline: Option<std::time::Instant>,
+    ) -> crate::error::Result<Option<usize>> {
+        if let Some(deadline) = deadline {
+            let remaining =
+                deadline.saturating_duration_since(std::time::Instant::now());
+            if !self.source.poll(remaining)? {
+                return Ok(None);
+            }
+        }
+        let cur = self.buf.len();
+        self.buf.resize(cur + 4096, 0);
+        let res = self.source.read(&mut self.buf[cur..]);
+        let bytes = res.inspect_err(|_| self.buf.truncate(cur))?;
+        self.buf.truncate(cur + bytes);
+        self.eof = bytes == 0;
+        Ok(Some(bytes))
     }
+
+    fn query(
+        &mut self,
+        output: &mut super::Output,
+        request: &[u8],
+        final_byte: u8,
+        nparams: usize,
+        timeout: std::time::Duration,
+    ) -> crate::error::Result<Option<Vec<u32>>> {
+        if matches!(self.source, Source::Reader(_)) {
+            return Err(crate::error::Error::ReadStdin(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "can't wait for a reply from a reader with a timeout",
+            )));
+        }
+        output.write_raw(request)?;
+        let deadline = std::time::Instant::now() + timeout;
+        if self.buf_is_empty() {
+            self.buf.clear();
+            self.pos = 0;
+        }
+        loop {
+            if let Some(params) = self.take_csi_reply(final_byte, nparams) {
+                return Ok(Some(params));
+            }
+            let remaining =
+                deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() || !self.source.poll(remaining)? {
+                return Ok(None);
+            }
+            let cur = self.buf.len();
+            self.buf.resize(cur + 4096, 0);
+            let bytes = self.source.read(&mut self.buf[cur..])?;
+            self.buf.truncate(cur + bytes);
+            if bytes == 0 {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+enum Source {
+    Stdin,
+    Tty(std::fs::File),
+    Reader(Box<dyn std::io::Read + Send>),
 }
 
-fn read_stdin(buf: &mut [u8]) -> crate::error::Result<usize> {
-    std::io::stdin()
-        .read(buf)
+impl Source {
+    fn read(&mut self, buf: &mut [u8]) -> crate::error::Result<usize> {
+        match self {
+            // std's stdin is buffered, which would hide data from poll
+            Self::Stdin => rustix::io::read(std::io::stdin(), buf)
+                .map_err(std::io::Error::from),
+            Self::Tty(tty) => tty.read(buf),
+            Self::Reader(reader) => reader.read(buf),
+        }
         .map_err(crate::error::Error::ReadStdin)
+    }
+
+    // returns true if the source became readable before the timeout
+    // expired (readers which aren't file descriptors are always considered
+    // readable)
+    fn poll(
+        &self,
+        timeout: std::time::Duration,
+    ) -> crate::error::Result<bool> {
+        match self {
+            Self::Stdin => poll_fd(std::io::stdin(), timeout),
+            Self::Tty(tty) => poll_fd(tty, timeout),
+            Self::Reader(_) => Ok(true),
+        }
+    }
+}
+
+pub fn make_raw(
+    fd: impl std::os::fd::AsFd,
+) -> crate::error::Result<rustix::termios::Termios> {
+    let termios = rustix::termios::tcgetattr(&fd)
+        .map_err(crate::error::Error::SetTerminalMode)?;
+    let mut termios_raw = termios.clone();
+    termios_raw.make_raw();
+    rustix::termios::tcsetattr(
+        &fd,
+        rustix::termios::OptionalActions::Now,
+        &termios_raw,
+    )
+    .map_err(crate::error::Error::SetTerminalMode)?;
+    Ok(termios)
+}
+
+pub fn restore(
+    fd: impl std::os::fd::AsFd,
+    termios: &rustix::termios::Termios,
+) -> crate::error::Result<()> {
+    rustix::termios::tcsetattr(
+        fd,
+        rustix::termios::OptionalActions::Now,
+        termios,
+    )
+    .map_err(crate::error::Error::SetTerminalMode)
+}
+
+fn poll_fd(
+    fd: impl std::os::fd::AsFd,
+    timeout: std::time::Duration,
+) -> crate::error::Result<bool> {
+    let mut fds = [rustix::event::PollFd::new(
+        &fd,
+        rustix::event::PollFlags::IN,
+    )];
+    let timeout = i32::try_from(timeout.as_nanos().div_ceil(1_000_000))
+        .unwrap_or(i32::MAX);
+    loop {
+        match rustix::event::poll(&mut fds, timeout) {
+            Ok(n) => return Ok(n > 0),
+            Err(rustix::io::Errno::INTR) => {}
+            Err(e) => return Err(crate::error::Error::ReadStdin(e.into())),
+        }
+    }
 }
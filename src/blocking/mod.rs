@@ -1,4 +1,6 @@
 pub(crate) mod input;
 pub use input::{Input, RawGuard};
-mod output;
+pub(crate) mod output;
 pub use output::{Output, ScreenGuard};
+mod resize;
+pub use resize::ResizeWatcher;
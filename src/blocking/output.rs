@@ -1,11 +1,15 @@
 use std::io::Write as _;
 
 use crate::private::Output as _;
+use crate::Textmode as _;
 
-/// Switches the terminal on `stdout` to alternate screen mode, and restores
-/// it when this object goes out of scope.
+/// Switches the terminal on `stdout` (or another terminal) to alternate
+/// screen mode, and restores it when this object goes out of scope.
 pub struct ScreenGuard {
+    // None means stdout
+    tty: Option<std::fs::File>,
     cleaned_up: bool,
+    bracketed_paste: bool,
 }
 
 impl ScreenGuard {
@@ -16,8 +20,49 @@ impl ScreenGuard {
     /// # Errors
     /// * `Error::WriteStdout`: failed to write initialization to stdout
     pub fn new() -> crate::error::Result<Self> {
-        write_stdout(crate::INIT)?;
-        Ok(Self { cleaned_up: false })
+        Self::new_for_tty(None)
+    }
+
+    /// Switches the given terminal to alternate screen mode and returns a
+    /// guard object. This is typically called as part of
+    /// [`Output::from_fd`](Output::from_fd).
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to duplicate the file descriptor
+    /// * `Error::WriteStdout`: failed to write initialization to the
+    ///   terminal
+    pub fn from_fd(fd: impl std::os::fd::AsFd) -> crate::error::Result<Self> {
+        let tty = fd
+            .as_fd()
+            .try_clone_to_owned()
+            .map_err(crate::error::Error::OpenTerminal)?;
+        Self::new_for_tty(Some(tty.into()))
+    }
+
+    fn new_for_tty(tty: Option<std::fs::File>) -> crate::error::Result<Self> {
+        let self_ = Self {
+            tty,
+            cleaned_up: false,
+            bracketed_paste: false,
+        };
+        self_.write(crate::INIT)?;
+        Ok(self_)
+    }
+
+    /// Enables bracketed paste mode, by writing the appropriate escape
+    /// sequence to `stdout`. This causes pasted text to be reported as a
+    /// single [`Paste`](crate::Key::Paste) key by `read_key`, rather than as
+    /// individual keypresses. Bracketed paste mode will be disabled again
+    /// when this guard is cleaned up.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to stdout
+    pub fn enable_bracketed_paste(&mut self) -> crate::error::Result<()> {
+        if !self.bracketed_paste {
+            self.write(crate::BRACKETED_PASTE_ENABLE)?;
+            self.bracketed_paste = true;
+        }
+        Ok(())
     }
 
     /// Switch back from alternate screen mode early.
@@ -29,7 +74,22 @@ impl ScreenGuard {
             return Ok(());
         }
         self.cleaned_up = true;
-        write_stdout(crate::DEINIT)
+        self.write(&self.deinit_sequence())
+    }
+
+    fn write(&self, buf: &[u8]) -> crate::error::Result<()> {
+        self.tty
+            .as_ref()
+            .map_or_else(|| write_stdout(buf), |tty| write_tty(tty, buf))
+    }
+
+    fn deinit_sequence(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        if self.bracketed_paste {
+            buf.extend(crate::BRACKETED_PASTE_DISABLE);
+        }
+        buf.extend(crate::DEINIT);
+        buf
     }
 }
 
@@ -40,17 +100,30 @@ impl Drop for ScreenGuard {
     }
 }
 
-/// Manages drawing to the terminal on `stdout`.
+/// Manages drawing to the terminal on `stdout` (or another terminal or
+/// writer).
 ///
 /// Most functionality is provided by the [`Textmode`](crate::Textmode) trait.
 /// You should call those trait methods to draw to the in-memory screen, and
 /// then call [`refresh`](Output::refresh) when you want to update the
 /// terminal on `stdout`.
+///
+/// By default, the in-memory screen covers the whole terminal. Alternatively,
+/// [`new_inline`](Output::new_inline) creates an `Output` which only draws
+/// to a fixed number of lines starting at the current cursor position,
+/// without switching to the alternate screen, which is useful for things
+/// like prompts and progress displays.
 pub struct Output {
+    sink: Sink,
     screen: Option<ScreenGuard>,
+    resize: Option<crate::resize::ResizeFlag>,
+    color_depth: crate::ColorDepth,
+    inline: Option<crate::InlineCleanup>,
 
     cur: vt100::Parser,
     next: vt100::Parser,
+    ext: crate::ext::Ext,
+    lines: crate::draw::Lines,
 }
 
 impl crate::private::Output for Output {
@@ -69,6 +142,18 @@ impl crate::private::Output for Output {
     fn next_mut(&mut self) -> &mut vt100::Parser {
         &mut self.next
     }
+
+    fn ext(&self) -> &crate::ext::Ext {
+        &self.ext
+    }
+
+    fn ext_mut(&mut self) -> &mut crate::ext::Ext {
+        &mut self.ext
+    }
+
+    fn lines_mut(&mut self) -> &mut crate::draw::Lines {
+        &mut self.lines
+    }
 }
 
 impl crate::Textmode for Output {}
@@ -85,23 +170,85 @@ impl Output {
         Ok(self_)
     }
 
+    /// Creates a new `Output` instance which draws to the given terminal
+    /// instead of `stdout`, containing a [`ScreenGuard`](ScreenGuard)
+    /// instance for that terminal. This can be used to draw to a pty, or to
+    /// keep `stdout` free for other data.
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to duplicate the file descriptor
+    /// * `Error::WriteStdout`: failed to write initialization to the
+    ///   terminal
+    pub fn from_fd(fd: impl std::os::fd::AsFd) -> crate::error::Result<Self> {
+        let tty: std::fs::File = fd
+            .as_fd()
+            .try_clone_to_owned()
+            .map_err(crate::error::Error::OpenTerminal)?
+            .into();
+        let screen = ScreenGuard::from_fd(&tty)?;
+        let mut self_ = Self::new_for_sink(Sink::Tty(tty));
+        self_.screen = Some(screen);
+        Ok(self_)
+    }
+
+    /// Creates a new `Output` instance which draws to the controlling
+    /// terminal (`/dev/tty`) instead of `stdout`. See
+    /// [`from_fd`](Output::from_fd).
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to open `/dev/tty`
+    /// * `Error::WriteStdout`: failed to write initialization to the
+    ///   terminal
+    pub fn from_tty() -> crate::error::Result<Self> {
+        Self::from_fd(open_tty()?)
+    }
+
+    /// Creates a new `Output` instance which writes to the given writer,
+    /// without creating a [`ScreenGuard`](ScreenGuard) instance. Since the
+    /// size of the terminal can't be determined from an arbitrary writer,
+    /// the in-memory screen starts out as 24x80, and should be resized with
+    /// [`set_size`](crate::Textmode::set_size) if necessary.
+    #[must_use]
+    pub fn from_writer(writer: impl std::io::Write + Send + 'static) -> Self {
+        Self::new_for_sink(Sink::Writer(Box::new(writer)))
+    }
+
     /// Creates a new `Output` instance without creating a
     /// [`ScreenGuard`](ScreenGuard) instance.
     #[must_use]
     pub fn new_without_screen() -> Self {
-        let (rows, cols) = match terminal_size::terminal_size() {
-            Some((terminal_size::Width(w), terminal_size::Height(h))) => {
-                (h, w)
-            }
-            _ => (24, 80),
-        };
+        Self::new_for_sink(Sink::Stdout)
+    }
+
+    /// Creates a new `Output` instance which draws inline on `stdout`. See
+    /// [`inline`](Output::inline).
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write initialization to stdout
+    pub fn new_inline(
+        rows: u16,
+        cleanup: crate::InlineCleanup,
+    ) -> crate::error::Result<Self> {
+        let mut self_ = Self::new_without_screen();
+        self_.inline(rows, cleanup)?;
+        Ok(self_)
+    }
+
+    fn new_for_sink(sink: Sink) -> Self {
+        let (rows, cols) = sink.size().unwrap_or((24, 80));
         let cur = vt100::Parser::new(rows, cols, 0);
         let next = vt100::Parser::new(rows, cols, 0);
 
         Self {
+            sink,
             screen: None,
+            resize: None,
+            color_depth: crate::ColorDepth::detect(),
+            inline: None,
             cur,
             next,
+            ext: crate::ext::Ext::default(),
+            lines: crate::draw::Lines::default(),
         }
     }
 
@@ -113,6 +260,101 @@ impl Output {
         self.screen.take()
     }
 
+    /// Enables bracketed paste mode. This is done through the
+    /// [`ScreenGuard`](ScreenGuard) instance stored in this `Output`
+    /// instance, and so does nothing if there isn't one; in that case, call
+    /// [`ScreenGuard::enable_bracketed_paste`] directly instead.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to stdout
+    pub fn enable_bracketed_paste(&mut self) -> crate::error::Result<()> {
+        if let Some(screen) = &mut self.screen {
+            screen.enable_bracketed_paste()?;
+        }
+        Ok(())
+    }
+
+    /// Sets whether this `Output` should automatically handle the terminal
+    /// being resized. When enabled, the next call to
+    /// [`refresh`](Output::refresh) or
+    /// [`hard_refresh`](Output::hard_refresh) after a `SIGWINCH` is received
+    /// will update the size of the in-memory screen to match the terminal
+    /// and redraw the entire screen. Defaults to false.
+    ///
+    /// # Errors
+    /// * `Error::RegisterSignal`: failed to register the signal handler
+    pub fn auto_resize(&mut self, enable: bool) -> crate::error::Result<()> {
+        if !enable {
+            self.resize = None;
+        } else if self.resize.is_none() {
+            self.resize = Some(crate::resize::ResizeFlag::new()?);
+        }
+        Ok(())
+    }
+
+    /// Sets the color depth of the terminal. Colors in the in-memory screen
+    /// which the terminal can't display are converted to the closest color
+    /// it can display when the screen is drawn to the terminal. Defaults to
+    /// the result of [`ColorDepth::detect`](crate::ColorDepth::detect).
+    pub fn color_depth(&mut self, depth: crate::ColorDepth) {
+        self.color_depth = depth;
+    }
+
+    /// Switches this `Output` to drawing inline, rather than over the whole
+    /// terminal. This reserves `rows` lines starting at the line the cursor
+    /// is currently on (scrolling the terminal up if there aren't enough
+    /// lines below the cursor), and resizes the in-memory screen to cover
+    /// just those lines. Since this doesn't use the alternate screen, this
+    /// should be used on an `Output` without a [`ScreenGuard`](ScreenGuard).
+    ///
+    /// When the `Output` is cleaned up (either explicitly with
+    /// [`cleanup`](Output::cleanup) or when it is dropped), the region is
+    /// either left in place or erased, depending on `cleanup`.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write initialization to stdout
+    pub fn inline(
+        &mut self,
+        rows: u16,
+        cleanup: crate::InlineCleanup,
+    ) -> crate::error::Result<()> {
+        let (term_rows, cols) = self.sink.size().unwrap_or_else(|| {
+            let (rows, cols) = self.cur().screen().size();
+            (rows.max(1), cols)
+        });
+        let rows = rows.clamp(1, term_rows);
+        self.sink.write(&crate::inline::init_sequence(rows))?;
+        self.set_size(rows, cols);
+        // the region is blank with the cursor at its start, regardless of
+        // what was drawn before
+        self.cur = vt100::Parser::new(rows, cols, 0);
+        self.inline = Some(cleanup);
+        Ok(())
+    }
+
+    /// Cleans up the terminal early: the inline region is left in place or
+    /// erased (if [`inline`](Output::inline) was used), and the
+    /// [`ScreenGuard`](ScreenGuard) (if any) is cleaned up. The in-memory
+    /// screen is left as it is (including the size it had while drawing
+    /// inline), and further calls to [`refresh`](Output::refresh) will draw
+    /// it using absolute cursor positions, from the top left of the
+    /// terminal.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write deinitialization to stdout
+    pub fn cleanup(&mut self) -> crate::error::Result<()> {
+        if let Some(cleanup) = self.inline.take() {
+            let (row, _) = self.cur().screen().cursor_position();
+            let (rows, _) = self.cur().screen().size();
+            self.sink
+                .write(&crate::inline::deinit_sequence(cleanup, row, rows))?;
+        }
+        if let Some(screen) = &mut self.screen {
+            screen.cleanup()?;
+        }
+        Ok(())
+    }
+
     /// Draws the in-memory screen to the terminal on `stdout`. This is done
     /// using a diff mechanism to only update the parts of the terminal which
     /// are different from the in-memory screen.
@@ -120,10 +362,14 @@ impl Output {
     /// # Errors
     /// * `Error::WriteStdout`: failed to write screen state to stdout
     pub fn refresh(&mut self) -> crate::error::Result<()> {
+        if self.check_resize() {
+            return self.hard_refresh();
+        }
         let diff = self.next().screen().state_diff(self.cur().screen());
-        write_stdout(&diff)?;
-        self.cur_mut().process(&diff);
-        Ok(())
+        let diff =
+            self.ext
+                .draw(diff, self.next.screen(), self.cur.screen(), false);
+        self.write_screen_state(&diff)
     }
 
     /// Draws the in-memory screen to the terminal on `stdout`. This clears
@@ -135,14 +381,99 @@ impl Output {
     /// # Errors
     /// * `Error::WriteStdout`: failed to write screen state to stdout
     pub fn hard_refresh(&mut self) -> crate::error::Result<()> {
+        self.check_resize();
         let contents = self.next().screen().state_formatted();
-        write_stdout(&contents)?;
-        self.cur_mut().process(&contents);
-        Ok(())
+        let contents = self.ext.draw(
+            contents,
+            self.next.screen(),
+            self.cur.screen(),
+            true,
+        );
+        self.write_screen_state(&contents)
+    }
+
+    // writes directly to the terminal, bypassing the in-memory screen
+    pub(crate) fn write_raw(
+        &mut self,
+        buf: &[u8],
+    ) -> crate::error::Result<()> {
+        self.sink.write(buf)
+    }
+
+    fn write_screen_state(&mut self, buf: &[u8]) -> crate::error::Result<()> {
+        if self.inline.is_some() {
+            let buf = crate::inline::translate(buf, self.cur_mut());
+            self.sink
+                .write(&crate::color_depth::downgrade(&buf, self.color_depth))
+        } else {
+            self.sink.write(&crate::color_depth::downgrade(
+                buf,
+                self.color_depth,
+            ))?;
+            self.cur_mut().process(buf);
+            Ok(())
+        }
+    }
+
+    fn check_resize(&mut self) -> bool {
+        if self
+            .resize
+            .as_ref()
+            .is_some_and(crate::resize::ResizeFlag::take)
+        {
+            if let Some((rows, cols)) = self.sink.size() {
+                // inline regions keep their height, since the lines around
+                // them belong to someone else
+                let rows = if self.inline.is_some() {
+                    self.cur().screen().size().0.min(rows)
+                } else {
+                    rows
+                };
+                self.set_size(rows, cols);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Drop for Output {
+    /// Calls `cleanup`.
+    fn drop(&mut self) {
+        let _ = self.cleanup();
+    }
+}
+
+enum Sink {
+    Stdout,
+    Tty(std::fs::File),
+    Writer(Box<dyn std::io::Write + Send>),
+}
+
+impl Sink {
+    fn write(&mut self, buf: &[u8]) -> crate::error::Result<()> {
+        match self {
+            Self::Stdout => write_stdout(buf),
+            Self::Tty(tty) => write_tty(tty, buf),
+            Self::Writer(writer) => {
+                writer
+                    .write_all(buf)
+                    .map_err(crate::error::Error::WriteStdout)?;
+                writer.flush().map_err(crate::error::Error::WriteStdout)
+            }
+        }
+    }
+
+    fn size(&self) -> Option<(u16, u16)> {
+        match self {
+            Self::Stdout => Some(crate::terminal_size()),
+            Self::Tty(tty) => crate::terminal_size_of(tty),
+            Self::Writer(_) => None,
+        }
     }
 }
 
-fn write_stdout(buf: &[u8]) -> crate::error::Result<()> {
+pub fn write_stdout(buf: &[u8]) -> crate::error::Result<()> {
     let mut stdout = std::io::stdout();
     stdout
         .write_all(buf)
@@ -150,3 +481,20 @@ fn write_stdout(buf: &[u8]) -> crate::error::Result<()> {
     stdout.flush().map_err(crate::error::Error::WriteStdout)?;
     Ok(())
 }
+
+pub fn write_tty(
+    mut tty: &std::fs::File,
+    buf: &[u8],
+) -> crate::error::Result<()> {
+    tty.write_all(buf)
+        .map_err(crate::error::Error::WriteStdout)?;
+    tty.flush().map_err(crate::error::Error::WriteStdout)
+}
+
+pub fn open_tty() -> crate::error::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .map_err(crate::error::Error::OpenTerminal)
+}
@@ -0,0 +1,43 @@
+/// Watches for the terminal being resized.
+///
+/// This listens for `SIGWINCH`, and reports the new size of the terminal on
+/// `stdout` each time it is received. Note that if you just want the
+/// [`Output`](crate::blocking::Output) to update itself, you can use
+/// [`auto_resize`](crate::blocking::Output::auto_resize) instead.
+pub struct ResizeWatcher {
+    signals: signal_hook::iterator::Signals,
+}
+
+impl ResizeWatcher {
+    /// Starts listening for `SIGWINCH`.
+    ///
+    /// # Errors
+    /// * `Error::RegisterSignal`: failed to register the signal handler
+    pub fn new() -> crate::error::Result<Self> {
+        let signals = signal_hook::iterator::Signals::new([
+            signal_hook::consts::SIGWINCH,
+        ])
+        .map_err(crate::error::Error::RegisterSignal)?;
+        Ok(Self { signals })
+    }
+
+    /// Blocks until the terminal is resized, and returns the new size as
+    /// `(rows, cols)`. Multiple resizes which happen before this is called
+    /// may be combined into a single notification.
+    pub fn recv(&mut self) -> (u16, u16) {
+        // forever() only returns None after the handle is closed, which
+        // never happens here
+        let _ = self.signals.forever().next();
+        crate::terminal_size()
+    }
+
+    /// Returns the new size of the terminal as `(rows, cols)` if it has been
+    /// resized since the last call to `recv` or `try_recv`, and `None`
+    /// otherwise. This never blocks.
+    pub fn try_recv(&mut self) -> Option<(u16, u16)> {
+        self.signals
+            .pending()
+            .last()
+            .map(|_| crate::terminal_size())
+    }
+}
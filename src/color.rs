@@ -16,3 +16,103 @@ pub const LIGHTBLUE: crate::Color = crate::Color::Idx(12);
 pub const LIGHTMAGENTA: crate::Color = crate::Color::Idx(13);
 pub const LIGHTCYAN: crate::Color = crate::Color::Idx(14);
 pub const WHITE: crate::Color = crate::Color::Idx(15);
+
+/// Serialization support for [`Color`](crate::Color), for use with
+/// `#[serde(with = "textmode::color::serde")]`.
+///
+/// `Color` is defined by the `vt100` crate, so `Serialize` and `Deserialize`
+/// can't be implemented for it directly. Colors are represented as
+/// `"default"` for [`Default`](crate::Color::Default), as a number for
+/// [`Idx`](crate::Color::Idx), and as a `"#rrggbb"` string for
+/// [`Rgb`](crate::Color::Rgb).
+///
+/// ```
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Theme {
+///     #[serde(with = "textmode::color::serde")]
+///     highlight: textmode::Color,
+/// }
+/// ```
+#[cfg(feature = "serde")]
+pub mod serde {
+    /// Serializes a [`Color`](crate::Color).
+    ///
+    /// # Errors
+    /// Returns any error from the serializer.
+    pub fn serialize<S: ::serde::Serializer>(
+        color: &crate::Color,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match color {
+            crate::Color::Default => serializer.serialize_str("default"),
+            crate::Color::Idx(i) => serializer.serialize_u8(*i),
+            crate::Color::Rgb(r, g, b) => serializer
+                .collect_str(&format_args!("#{r:02x}{g:02x}{b:02x}")),
+        }
+    }
+
+    /// Deserializes a [`Color`](crate::Color).
+    ///
+    /// # Errors
+    /// Returns an error if the value isn't a valid color representation.
+    pub fn deserialize<'de, D: ::serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<crate::Color, D::Error> {
+        deserializer.deserialize_any(Visitor)
+    }
+
+    struct Visitor;
+
+    impl ::serde::de::Visitor<'_> for Visitor {
+        type Value = crate::Color;
+
+        fn expecting(
+            &self,
+            f: &mut std::fmt::Formatter<'_>,
+        ) -> std::fmt::Result {
+            f.write_str("\"default\", a color index, or \"#rrggbb\"")
+        }
+
+        fn visit_u64<E: ::serde::de::Error>(
+            self,
+            v: u64,
+        ) -> Result<Self::Value, E> {
+            u8::try_from(v).map(crate::Color::Idx).map_err(|_| {
+                E::invalid_value(::serde::de::Unexpected::Unsigned(v), &self)
+            })
+        }
+
+        fn visit_i64<E: ::serde::de::Error>(
+            self,
+            v: i64,
+        ) -> Result<Self::Value, E> {
+            u8::try_from(v).map(crate::Color::Idx).map_err(|_| {
+                E::invalid_value(::serde::de::Unexpected::Signed(v), &self)
+            })
+        }
+
+        fn visit_str<E: ::serde::de::Error>(
+            self,
+            v: &str,
+        ) -> Result<Self::Value, E> {
+            if v == "default" {
+                return Ok(crate::Color::Default);
+            }
+            let rgb = v
+                .strip_prefix('#')
+                .filter(|hex| hex.len() == 6 && hex.is_ascii())
+                .and_then(|hex| {
+                    let component =
+                        |i| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+                    Some(crate::Color::Rgb(
+                        component(0)?,
+                        component(2)?,
+                        component(4)?,
+                    ))
+                });
+            rgb.ok_or_else(|| {
+                E::invalid_value(::serde::de::Unexpected::Str(v), &self)
+            })
+        }
+    }
+}
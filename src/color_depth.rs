@@ -0,0 +1,311 @@
+/// The number of colors supported by a terminal.
+///
+/// [`Output`](crate::blocking::Output) uses this to convert colors which the
+/// terminal can't display into the closest color that it can, when drawing
+/// the in-memory screen to the terminal. The in-memory screen itself always
+/// keeps the colors exactly as they were drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorDepth {
+    /// No colors at all. All color attributes are dropped.
+    Monochrome,
+    /// The basic 16 colors (the 8 standard colors and their bright
+    /// variants).
+    Color16,
+    /// The 256 color xterm palette.
+    Color256,
+    /// 24-bit RGB colors. No conversion is done.
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Detects the color depth of the current terminal from the environment.
+    ///
+    /// A `$COLORTERM` of `truecolor` or `24bit` indicates
+    /// [`TrueColor`](Self::TrueColor). Otherwise, the terminfo entry for
+    /// `$TERM` is consulted (the `RGB` and `Tc` extended capabilities and the
+    /// `colors` capability), falling back to guessing based on the name in
+    /// `$TERM` itself if no terminfo entry can be found. If `$TERM` isn't set
+    /// at all, this returns [`TrueColor`](Self::TrueColor), so that colors
+    /// are passed through unchanged.
+    #[must_use]
+    pub fn detect() -> Self {
+        if std::env::var("COLORTERM").is_ok_and(|colorterm| {
+            matches!(&*colorterm, "truecolor" | "24bit")
+        }) {
+            return Self::TrueColor;
+        }
+        let Ok(term) = std::env::var("TERM") else {
+            return Self::TrueColor;
+        };
+        crate::terminfo::Terminfo::load(&term).map_or_else(
+            || Self::from_term_name(&term),
+            |terminfo| Self::from_terminfo(&terminfo),
+        )
+    }
+
+    fn from_terminfo(terminfo: &crate::terminfo::Terminfo) -> Self {
+        if terminfo.has_ext_boolean("RGB") || terminfo.has_ext_boolean("Tc") {
+            return Self::TrueColor;
+        }
+        match terminfo.max_colors() {
+            Some(0x0100_0000..) => Self::TrueColor,
+            Some(256..) => Self::Color256,
+            Some(8..) => Self::Color16,
+            _ => Self::Monochrome,
+        }
+    }
+
+    fn from_term_name(term: &str) -> Self {
+        if term.ends_with("-direct") || term.contains("truecolor") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Color256
+        } else if term == "dumb" {
+            Self::Monochrome
+        } else {
+            Self::Color16
+        }
+    }
+}
+
+// the colors that the basic 16 color indexes are typically displayed as (the
+// xterm defaults)
+const BASIC_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+#[derive(Clone, Copy)]
+enum Target {
+    Fg,
+    Bg,
+    Underline,
+}
+
+// rewrites the colors in every SGR sequence in buf to fit in the given color
+// depth, leaving everything else alone
+pub fn downgrade(
+    buf: &[u8],
+    depth: ColorDepth,
+) -> std::borrow::Cow<'_, [u8]> {
+    if depth == ColorDepth::TrueColor {
+        return std::borrow::Cow::Borrowed(buf);
+    }
+
+    let mut out = Vec::with_capacity(buf.len());
+    let mut i = 0;
+    while i < buf.len() {
+        if buf[i..].starts_with(b"\x1b[") {
+            let start = i + 2;
+            let len = buf[start..]
+                .iter()
+                .position(|c| !matches!(c, b'0'..=b'9' | b';' | b':'));
+            if let Some(len) = len {
+                if buf[start + len] == b'm' {
+                    let params = &buf[start..start + len];
+                    // if every parameter was a color which got dropped,
+                    // drop the whole sequence, since an empty SGR sequence
+                    // would reset all of the attributes instead
+                    if let Some(params) = downgrade_params(params, depth) {
+                        out.extend(b"\x1b[");
+                        out.extend(params);
+                        out.push(b'm');
+                    }
+                    i = start + len + 1;
+                    continue;
+                }
+            }
+        }
+        out.push(buf[i]);
+        i += 1;
+    }
+    std::borrow::Cow::Owned(out)
+}
+
+fn downgrade_params(params: &[u8], depth: ColorDepth) -> Option<Vec<u8>> {
+    if params.is_empty() {
+        return Some(vec![]);
+    }
+
+    let params: Vec<&[u8]> = params.split(|&c| c == b';').collect();
+    let mut out: Vec<Vec<u8>> = vec![];
+    let mut i = 0;
+    while i < params.len() {
+        let param = params[i];
+        i += 1;
+
+        let target = match param.split(|&c| c == b':').next().and_then(parse)
+        {
+            Some(38) => Target::Fg,
+            Some(48) => Target::Bg,
+            Some(58) => Target::Underline,
+            // basic colors and default colors only need to be dropped in
+            // monochrome mode
+            Some(30..=37 | 39 | 40..=47 | 49 | 59 | 90..=97 | 100..=107)
+                if depth == ColorDepth::Monochrome =>
+            {
+                continue;
+            }
+            _ => {
+                out.push(param.to_vec());
+                continue;
+            }
+        };
+
+        // extended colors can be given either as separate parameters
+        // (38;5;n) or as subparameters (38:5:n or 38:2::r:g:b)
+        let color = if param.contains(&b':') {
+            let subparams: Vec<_> = param.split(|&c| c == b':').collect();
+            match subparams.get(1).copied().and_then(parse) {
+                Some(5) => {
+                    subparams.get(2).copied().and_then(parse).map(Color::Idx)
+                }
+                Some(2) => {
+                    // the color space id is optional
+                    let rgb = &subparams[subparams.len().saturating_sub(3)..];
+                    (subparams.len() >= 5)
+                        .then(|| {
+                            Some(Color::Rgb(
+                                parse(rgb[0])?,
+                                parse(rgb[1])?,
+                                parse(rgb[2])?,
+                            ))
+                        })
+                        .flatten()
+                }
+                _ => None,
+            }
+        } else {
+            match params.get(i).copied().and_then(parse) {
+                Some(5) => {
+                    let color = params
+                        .get(i + 1)
+                        .copied()
+                        .and_then(parse)
+                        .map(Color::Idx);
+                    i += 2;
+                    color
+                }
+                Some(2) => {
+                    let color = (|| {
+                        Some(Color::Rgb(
+                            parse(params.get(i + 1)?)?,
+                            parse(params.get(i + 2)?)?,
+                            parse(params.get(i + 3)?)?,
+                        ))
+                    })();
+                    i += 4;
+                    color
+                }
+                _ => None,
+            }
+        };
+
+        if let Some(color) = color {
+            if let Some(param) = downgrade_color(target, color, depth) {
+                out.push(param);
+            }
+        }
+    }
+
+    if out.is_empty() {
+        None
+    } else {
+        Some(out.join(&b';'))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Color {
+    Idx(u8),
+    Rgb(u8, u8, u8),
+}
+
+fn downgrade_color(
+    target: Target,
+    color: Color,
+    depth: ColorDepth,
+) -> Option<Vec<u8>> {
+    match depth {
+        ColorDepth::TrueColor => unreachable!(),
+        ColorDepth::Monochrome => None,
+        ColorDepth::Color256 => {
+            let idx = match color {
+                Color::Idx(idx) => idx,
+                Color::Rgb(r, g, b) => nearest(16..=255, (r, g, b)),
+            };
+            let prefix = match target {
+                Target::Fg => "38",
+                Target::Bg => "48",
+                Target::Underline => "58",
+            };
+            Some(format!("{prefix};5;{idx}").into_bytes())
+        }
+        ColorDepth::Color16 => {
+            let idx = match color {
+                Color::Idx(idx) if idx < 16 => idx,
+                Color::Idx(idx) => nearest(0..=15, palette(idx)),
+                Color::Rgb(r, g, b) => nearest(0..=15, (r, g, b)),
+            };
+            // there is no way to set the underline color without extended
+            // colors
+            let base = match target {
+                Target::Fg => 30,
+                Target::Bg => 40,
+                Target::Underline => return None,
+            };
+            let code = if idx < 8 { base + idx } else { base + 52 + idx };
+            Some(code.to_string().into_bytes())
+        }
+    }
+}
+
+// returns the index in the given range of the palette color closest to rgb
+fn nearest(range: std::ops::RangeInclusive<u8>, rgb: (u8, u8, u8)) -> u8 {
+    range
+        .min_by_key(|&idx| distance(palette(idx), rgb))
+        .unwrap_or(0)
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn palette(idx: u8) -> (u8, u8, u8) {
+    match idx {
+        0..=15 => BASIC_COLORS[usize::from(idx)],
+        16..=231 => {
+            let idx = idx - 16;
+            (
+                CUBE_LEVELS[usize::from(idx / 36)],
+                CUBE_LEVELS[usize::from(idx / 6 % 6)],
+                CUBE_LEVELS[usize::from(idx % 6)],
+            )
+        }
+        232..=255 => {
+            let level = 8 + (idx - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+fn parse(param: &[u8]) -> Option<u8> {
+    std::str::from_utf8(param).ok()?.parse().ok()
+}
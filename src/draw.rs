@@ -0,0 +1,288 @@
+/// The set of characters used by
+/// [`draw_hline`](crate::Draw::draw_hline),
+/// [`draw_vline`](crate::Draw::draw_vline), and
+/// [`draw_box`](crate::Draw::draw_box).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BorderStyle {
+    /// `┌─┐`
+    #[default]
+    Single,
+    /// `╔═╗`
+    Double,
+    /// `╭─╮`
+    Rounded,
+    /// `┏━┓`
+    Heavy,
+    /// `+-+`, for terminals which can't display the others.
+    Ascii,
+}
+
+const STYLES: [BorderStyle; 5] = [
+    BorderStyle::Single,
+    BorderStyle::Double,
+    BorderStyle::Rounded,
+    BorderStyle::Heavy,
+    BorderStyle::Ascii,
+];
+
+// the directions that a line leaves a cell in
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+impl BorderStyle {
+    // indexed by the set of directions
+    fn glyphs(self) -> &'static [char; 16] {
+        match self {
+            Self::Single => &[
+                ' ', '│', '│', '│', '─', '┘', '┐', '┤', '─', '└', '┌', '├',
+                '─', '┴', '┬', '┼',
+            ],
+            Self::Double => &[
+                ' ', '║', '║', '║', '═', '╝', '╗', '╣', '═', '╚', '╔', '╠',
+                '═', '╩', '╦', '╬',
+            ],
+            Self::Rounded => &[
+                ' ', '│', '│', '│', '─', '╯', '╮', '┤', '─', '╰', '╭', '├',
+                '─', '┴', '┬', '┼',
+            ],
+            Self::Heavy => &[
+                ' ', '┃', '┃', '┃', '━', '┛', '┓', '┫', '━', '┗', '┏', '┣',
+                '━', '┻', '┳', '╋',
+            ],
+            Self::Ascii => &[
+                ' ', '|', '|', '|', '-', '+', '+', '+', '-', '+', '+', '+',
+                '-', '+', '+', '+',
+            ],
+        }
+    }
+
+    fn glyph(self, dirs: u8) -> char {
+        self.glyphs()[usize::from(dirs)]
+    }
+}
+
+// guesses the directions that the line drawing character in a cell leaves
+// it in, for characters which weren't drawn by this module. a line segment
+// (rather than an end or a corner) looks the same as a line which only goes
+// in one direction, so it is assumed to go in both.
+fn guess_dirs(contents: &str) -> u8 {
+    let mut chars = contents.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return 0;
+    };
+    if c == '+' {
+        return UP | DOWN | LEFT | RIGHT;
+    }
+    STYLES
+        .iter()
+        .find_map(|style| {
+            style
+                .glyphs()
+                .iter()
+                .rposition(|&glyph| glyph == c)
+                .and_then(|dirs| u8::try_from(dirs).ok())
+        })
+        .filter(|&dirs| dirs != 0)
+        .unwrap_or(0)
+}
+
+// the directions that the lines drawn into each cell leave it in, since the
+// end of a line looks the same as the middle of one, and so the contents of
+// the screen aren't enough to join later lines up with it correctly. the
+// character drawn is kept too, so that cells which have since been
+// overwritten with something else are noticed.
+#[derive(Default)]
+pub struct Lines(std::collections::HashMap<(u16, u16), (char, u8)>);
+
+impl Lines {
+    fn dirs(&self, row: u16, col: u16, contents: &str) -> u8 {
+        let mut chars = contents.chars();
+        match (self.0.get(&(row, col)), chars.next(), chars.next()) {
+            (Some(&(glyph, dirs)), Some(c), None) if c == glyph => dirs,
+            _ => guess_dirs(contents),
+        }
+    }
+}
+
+// the region of the screen that drawing is restricted to
+#[derive(Clone, Copy)]
+pub struct Clip {
+    pub row: u16,
+    pub col: u16,
+    pub rows: u16,
+    pub cols: u16,
+}
+
+impl Clip {
+    pub fn screen(screen: &vt100::Screen) -> Self {
+        let (rows, cols) = screen.size();
+        Self {
+            row: 0,
+            col: 0,
+            rows,
+            cols,
+        }
+    }
+
+    // converts a position relative to the region into a screen position
+    pub fn offset(self, row: u16, col: u16) -> (u16, u16) {
+        (self.row.saturating_add(row), self.col.saturating_add(col))
+    }
+
+    fn contains(self, row: u16, col: u16) -> bool {
+        (self.row..self.row.saturating_add(self.rows)).contains(&row)
+            && (self.col..self.col.saturating_add(self.cols)).contains(&col)
+    }
+}
+
+// draws each of the given cells, joining it up with any line drawing
+// character already there. the cursor is left where it was.
+fn draw_cells<T: crate::Textmode>(
+    tm: &mut T,
+    style: BorderStyle,
+    clip: Clip,
+    cells: impl IntoIterator<Item = (u16, u16, u8)>,
+) {
+    let (cursor_row, cursor_col) = tm.screen().cursor_position();
+    for (row, col, dirs) in cells {
+        if !clip.contains(row, col) {
+            continue;
+        }
+        let contents = tm
+            .screen()
+            .cell(row, col)
+            .map(vt100::Cell::contents)
+            .unwrap_or_default();
+        let dirs = dirs | tm.lines_mut().dirs(row, col, &contents);
+        let glyph = style.glyph(dirs);
+        let mut buf = [0; 4];
+        tm.move_to(row, col);
+        tm.write_str(glyph.encode_utf8(&mut buf));
+        tm.lines_mut().0.insert((row, col), (glyph, dirs));
+    }
+    tm.move_to(cursor_row, cursor_col);
+}
+
+pub fn hline<T: crate::Textmode>(
+    tm: &mut T,
+    row: u16,
+    col: u16,
+    len: u16,
+    style: BorderStyle,
+    clip: Clip,
+) {
+    let last = len.saturating_sub(1);
+    draw_cells(
+        tm,
+        style,
+        clip,
+        (0..len).map(|i| {
+            let mut dirs = 0;
+            if i > 0 || len == 1 {
+                dirs |= LEFT;
+            }
+            if i < last || len == 1 {
+                dirs |= RIGHT;
+            }
+            (row, col.saturating_add(i), dirs)
+        }),
+    );
+}
+
+pub fn vline<T: crate::Textmode>(
+    tm: &mut T,
+    row: u16,
+    col: u16,
+    len: u16,
+    style: BorderStyle,
+    clip: Clip,
+) {
+    let last = len.saturating_sub(1);
+    draw_cells(
+        tm,
+        style,
+        clip,
+        (0..len).map(|i| {
+            let mut dirs = 0;
+            if i > 0 || len == 1 {
+                dirs |= UP;
+            }
+            if i < last || len == 1 {
+                dirs |= DOWN;
+            }
+            (row.saturating_add(i), col, dirs)
+        }),
+    );
+}
+
+// the corners are drawn along with the edges (rather than drawing four
+// separate lines) so that each corner is only drawn once.
+pub fn draw_box<T: crate::Textmode>(
+    tm: &mut T,
+    row: u16,
+    col: u16,
+    rows: u16,
+    cols: u16,
+    style: BorderStyle,
+    clip: Clip,
+) {
+    if rows == 0 || cols == 0 {
+        return;
+    }
+    if rows == 1 {
+        hline(tm, row, col, cols, style, clip);
+        return;
+    }
+    if cols == 1 {
+        vline(tm, row, col, rows, style, clip);
+        return;
+    }
+    let bottom = row.saturating_add(rows - 1);
+    let right = col.saturating_add(cols - 1);
+    let inner_cols = (1..cols - 1).map(|i| col.saturating_add(i));
+    let inner_rows = (1..rows - 1).map(|i| row.saturating_add(i));
+    let cells = [
+        (row, col, DOWN | RIGHT),
+        (row, right, DOWN | LEFT),
+        (bottom, col, UP | RIGHT),
+        (bottom, right, UP | LEFT),
+    ]
+    .into_iter()
+    .chain(inner_cols.clone().map(|c| (row, c, LEFT | RIGHT)))
+    .chain(inner_cols.map(|c| (bottom, c, LEFT | RIGHT)))
+    .chain(inner_rows.clone().map(|r| (r, col, UP | DOWN)))
+    .chain(inner_rows.map(|r| (r, right, UP | DOWN)));
+    draw_cells(tm, style, clip, cells);
+}
+
+pub fn fill_rect<T: crate::Textmode>(
+    tm: &mut T,
+    row: u16,
+    col: u16,
+    rows: u16,
+    cols: u16,
+    c: char,
+    clip: Clip,
+) {
+    let (cursor_row, cursor_col) = tm.screen().cursor_position();
+    let first_row = row.max(clip.row);
+    let last_row = row
+        .saturating_add(rows)
+        .min(clip.row.saturating_add(clip.rows));
+    let first_col = col.max(clip.col);
+    let last_col = col
+        .saturating_add(cols)
+        .min(clip.col.saturating_add(clip.cols));
+    if first_col < last_col {
+        let line: String =
+            std::iter::repeat_n(c, usize::from(last_col - first_col))
+                .collect();
+        for row in first_row..last_row {
+            tm.move_to(row, first_col);
+            tm.write_str(&line);
+        }
+    }
+    tm.move_to(cursor_row, cursor_col);
+}
@@ -9,6 +9,12 @@ pub enum Error {
 
     /// error writing to stdout
     WriteStdout(std::io::Error),
+
+    /// error registering a signal handler
+    RegisterSignal(std::io::Error),
+
+    /// error opening terminal
+    OpenTerminal(std::io::Error),
 }
 
 impl std::fmt::Display for Error {
@@ -23,6 +29,12 @@ impl std::fmt::Display for Error {
             Self::WriteStdout(e) => {
                 write!(f, "error writing to stdout: {e}")
             }
+            Self::RegisterSignal(e) => {
+                write!(f, "error registering signal handler: {e}")
+            }
+            Self::OpenTerminal(e) => {
+                write!(f, "error opening terminal: {e}")
+            }
         }
     }
 }
@@ -30,7 +42,10 @@ impl std::fmt::Display for Error {
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::ReadStdin(e) | Self::WriteStdout(e) => Some(e),
+            Self::ReadStdin(e)
+            | Self::WriteStdout(e)
+            | Self::RegisterSignal(e)
+            | Self::OpenTerminal(e) => Some(e),
             Self::SetTerminalMode(e) => Some(e),
         }
     }
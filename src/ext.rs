@@ -0,0 +1,533 @@
+// vt100 only keeps track of the colors and the bold, italic, underline, and
+// inverse attributes, and ignores the rest. to keep track of the rest, every
+// byte written to the in-memory screen is also written to a second vt100
+// parser, except that sgr sequences are replaced by ones which encode the
+// extra attributes as colors (the underline color as the foreground color,
+// and the rest as an indexed background color). since everything else is
+// passed through unchanged, the two parsers always agree about where text
+// is, and so each cell of the second parser holds the extra attributes of
+// the corresponding cell of the first.
+
+const DIM: u8 = 1 << 0;
+const BLINK: u8 = 1 << 1;
+const HIDDEN: u8 = 1 << 2;
+const STRIKETHROUGH: u8 = 1 << 3;
+const OVERLINE: u8 = 1 << 4;
+const UNDERLINE_SHIFT: u8 = 5;
+const UNDERLINE_MASK: u8 = 0b111 << UNDERLINE_SHIFT;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Attrs {
+    flags: u8,
+    pub underline_color: vt100::Color,
+}
+
+impl Attrs {
+    fn from_colors(fgcolor: vt100::Color, bgcolor: vt100::Color) -> Self {
+        let flags = match bgcolor {
+            vt100::Color::Idx(flags) => flags,
+            _ => 0,
+        };
+        Self {
+            flags,
+            underline_color: fgcolor,
+        }
+    }
+
+    fn is_default(self) -> bool {
+        self == Self::default()
+    }
+
+    pub fn dim(self) -> bool {
+        self.flags & DIM != 0
+    }
+
+    pub fn blink(self) -> bool {
+        self.flags & BLINK != 0
+    }
+
+    pub fn hidden(self) -> bool {
+        self.flags & HIDDEN != 0
+    }
+
+    pub fn strikethrough(self) -> bool {
+        self.flags & STRIKETHROUGH != 0
+    }
+
+    pub fn overline(self) -> bool {
+        self.flags & OVERLINE != 0
+    }
+
+    pub fn underline_style(self) -> Option<crate::UnderlineStyle> {
+        crate::UnderlineStyle::from_param(
+            (self.flags & UNDERLINE_MASK) >> UNDERLINE_SHIFT,
+        )
+    }
+
+    fn set(&mut self, flag: u8, value: bool) {
+        if value {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+    }
+
+    // 0 means no underline
+    fn set_underline(&mut self, param: u8) {
+        self.flags = (self.flags & !UNDERLINE_MASK)
+            | ((param << UNDERLINE_SHIFT) & UNDERLINE_MASK);
+    }
+
+    fn write_encoded(self, buf: &mut Vec<u8>) {
+        let mut itoa_buf = itoa::Buffer::new();
+        buf.extend(b"\x1b[0");
+        match self.underline_color {
+            vt100::Color::Default => {}
+            vt100::Color::Idx(i) => {
+                buf.extend(b";38;5;");
+                buf.extend(itoa_buf.format(i).as_bytes());
+            }
+            vt100::Color::Rgb(r, g, b) => {
+                buf.extend(b";38;2;");
+                buf.extend(itoa_buf.format(r).as_bytes());
+                buf.push(b';');
+                buf.extend(itoa_buf.format(g).as_bytes());
+                buf.push(b';');
+                buf.extend(itoa_buf.format(b).as_bytes());
+            }
+        }
+        if self.flags != 0 {
+            buf.extend(b";48;5;");
+            buf.extend(itoa_buf.format(self.flags).as_bytes());
+        }
+        buf.push(b'm');
+    }
+}
+
+#[derive(Default)]
+pub struct Ext {
+    // None until an extra attribute is first used, so that outputs which
+    // never use them don't pay for keeping track of them
+    mirror: Option<Mirror>,
+    // an escape sequence which hasn't been completely written yet
+    pending: Vec<u8>,
+}
+
+struct Mirror {
+    next: vt100::Parser,
+    // the extra attributes drawn on the terminal as of the last refresh
+    cur: vt100::Parser,
+}
+
+impl Ext {
+    pub fn set_size(&mut self, rows: u16, cols: u16) {
+        if let Some(mirror) = &mut self.mirror {
+            mirror.next.set_size(rows, cols);
+            mirror.cur.set_size(rows, cols);
+        }
+    }
+
+    // the attributes which are currently active
+    pub fn attrs(&self) -> Attrs {
+        self.mirror.as_ref().map_or_else(Attrs::default, |mirror| {
+            let screen = mirror.next.screen();
+            Attrs::from_colors(screen.fgcolor(), screen.bgcolor())
+        })
+    }
+
+    pub fn cell_attrs(&self, row: u16, col: u16) -> Attrs {
+        self.mirror.as_ref().map_or_else(Attrs::default, |mirror| {
+            cell_attrs(mirror.next.screen(), row, col)
+        })
+    }
+
+    // whether `buf` uses any extra attributes for the first time, in which
+    // case `start` needs to be called before it is processed
+    pub fn needs_mirror(&self, buf: &[u8]) -> bool {
+        if self.mirror.is_some()
+            || (self.pending.is_empty() && !buf.contains(&b'\x1b'))
+        {
+            return false;
+        }
+        let data = if self.pending.is_empty() {
+            std::borrow::Cow::Borrowed(buf)
+        } else {
+            std::borrow::Cow::Owned([&self.pending, buf].concat())
+        };
+        let mut found = false;
+        for_each_sgr(&data, |params| found |= is_extended(params));
+        found
+    }
+
+    // starts keeping track of the extra attributes, given the size and
+    // formatted contents of the in-memory screen so far. the mirror starts
+    // out with a copy of its text and cursor position, so that the two agree
+    // on where later text goes (although state which isn't part of the
+    // screen contents, like the scroll region, isn't copied).
+    pub fn start(&mut self, (rows, cols): (u16, u16), contents: &[u8]) {
+        let mut mirror = Mirror {
+            next: vt100::Parser::new(rows, cols, 0),
+            cur: vt100::Parser::new(rows, cols, 0),
+        };
+        // the contents only use attributes that vt100 understands, which all
+        // translate to the default extra attributes
+        mirror.process(contents);
+        self.mirror = Some(mirror);
+    }
+
+    // processes `buf`, and returns the bytes which should be written to the
+    // in-memory screen in its place (with the sgr parameters that vt100
+    // doesn't understand removed), or None if `buf` can be written as is
+    pub fn process(&mut self, buf: &[u8]) -> Option<Vec<u8>> {
+        if self.pending.is_empty() {
+            let len = complete_len(buf);
+            if self.mirror.is_none() && len == buf.len() {
+                return None;
+            }
+            self.pending.extend(&buf[len..]);
+            let buf = &buf[..len];
+            return Some(
+                self.mirror.as_mut().map_or_else(
+                    || buf.to_vec(),
+                    |mirror| mirror.process(buf),
+                ),
+            );
+        }
+
+        let mut data = std::mem::take(&mut self.pending);
+        data.extend(buf);
+        self.pending = data.split_off(complete_len(&data));
+        if let Some(mirror) = &mut self.mirror {
+            data = mirror.process(&data);
+        }
+        Some(data)
+    }
+
+    // returns `diff` (the bytes which update the terminal from `cur` to
+    // `next`, as generated by vt100) with the extra bytes needed to also
+    // draw the extra attributes. vt100 doesn't know about the extra
+    // attributes, so the terminal has to have them turned off while the
+    // diff is drawn, and then any cells whose extra attributes are out of
+    // date afterward are redrawn. if `hard` is set, the diff redraws the
+    // whole screen.
+    pub fn draw(
+        &mut self,
+        diff: Vec<u8>,
+        next: &vt100::Screen,
+        cur: &vt100::Screen,
+        hard: bool,
+    ) -> Vec<u8> {
+        let Some(mirror) = &mut self.mirror else {
+            return diff;
+        };
+        if hard {
+            let (rows, cols) = next.size();
+            mirror.cur = vt100::Parser::new(rows, cols, 0);
+        }
+        let ext_next = mirror.next.screen();
+        let ext_cur = mirror.cur.screen();
+
+        let mut buf = vec![];
+        let cur_attrs =
+            Attrs::from_colors(ext_cur.fgcolor(), ext_cur.bgcolor());
+        if !cur_attrs.is_default() {
+            buf.extend(cur.attributes_formatted());
+        }
+        buf.extend(diff);
+
+        let (rows, cols) = next.size();
+        let mut redrawn = false;
+        for row in 0..rows {
+            for col in 0..cols {
+                let Some(cell) = next.cell(row, col) else {
+                    continue;
+                };
+                if !cell.has_contents() || cell.is_wide_continuation() {
+                    continue;
+                }
+                let attrs = cell_attrs(ext_next, row, col);
+                let old_attrs = cell_attrs(ext_cur, row, col);
+                let changed = hard || cur.cell(row, col) != Some(cell);
+                // cells drawn by the diff are drawn without extra attributes
+                let stale =
+                    attrs != old_attrs || (changed && !attrs.is_default());
+                if !stale {
+                    continue;
+                }
+                write_position(&mut buf, row, col);
+                buf.extend(crate::Style::from_cell(cell, attrs).sgr());
+                buf.extend(cell.contents().as_bytes());
+                redrawn = true;
+            }
+        }
+
+        let attrs =
+            Attrs::from_colors(ext_next.fgcolor(), ext_next.bgcolor());
+        if redrawn {
+            let (row, col) = next.cursor_position();
+            write_position(&mut buf, row, col);
+        }
+        if redrawn || attrs != cur_attrs {
+            buf.extend(crate::Style::from_screen(next, attrs).sgr());
+        }
+
+        let ext_diff = ext_next.state_diff(ext_cur);
+        mirror.cur.process(&ext_diff);
+        buf
+    }
+}
+
+impl Mirror {
+    // writes `buf` to the mirror, and returns it with the sgr parameters
+    // that vt100 doesn't understand removed. `buf` must not end in the
+    // middle of an escape sequence.
+    fn process(&mut self, buf: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(buf.len());
+        let mut start = 0;
+        for_each_csi(buf, |csi, params, final_byte| {
+            if !is_sgr(params, final_byte) {
+                return;
+            }
+            self.next.process(&buf[start..csi.start]);
+            out.extend(&buf[start..csi.start]);
+            let screen = self.next.screen();
+            let (attrs, sgr) = translate_sgr(
+                params,
+                Attrs::from_colors(screen.fgcolor(), screen.bgcolor()),
+            );
+            let mut encoded = vec![];
+            attrs.write_encoded(&mut encoded);
+            self.next.process(&encoded);
+            if let Some(sgr) = sgr {
+                out.extend(b"\x1b[");
+                out.extend(sgr);
+                out.push(b'm');
+            }
+            start = csi.end;
+        });
+        self.next.process(&buf[start..]);
+        out.extend(&buf[start..]);
+        out
+    }
+}
+
+// calls `f` with the range, parameters, and final byte of each complete csi
+// sequence in `buf`
+fn for_each_csi(
+    buf: &[u8],
+    mut f: impl FnMut(std::ops::Range<usize>, &[u8], u8),
+) {
+    let mut i = 0;
+    while let Some(offset) = buf[i..].iter().position(|&c| c == b'\x1b') {
+        let start = i + offset;
+        if buf.get(start + 1) != Some(&b'[') {
+            i = start + 1;
+            continue;
+        }
+        let Some(len) = buf[start + 2..]
+            .iter()
+            .position(|c| (0x40..=0x7e).contains(c))
+        else {
+            return;
+        };
+        let end = start + 2 + len;
+        f(start..end + 1, &buf[start + 2..end], buf[end]);
+        i = end + 1;
+    }
+}
+
+fn for_each_sgr(buf: &[u8], mut f: impl FnMut(&[u8])) {
+    for_each_csi(buf, |_, params, final_byte| {
+        if is_sgr(params, final_byte) {
+            f(params);
+        }
+    });
+}
+
+fn is_sgr(params: &[u8], final_byte: u8) -> bool {
+    final_byte == b'm'
+        && params
+            .iter()
+            .all(|c| matches!(c, b'0'..=b'9' | b';' | b':'))
+}
+
+// the length of `buf` without any escape sequence at the end which hasn't
+// been completely written yet
+fn complete_len(buf: &[u8]) -> usize {
+    let Some(start) = buf.iter().rposition(|&c| c == b'\x1b') else {
+        return buf.len();
+    };
+    match buf.get(start + 1) {
+        None => start,
+        Some(b'[') => {
+            if buf[start + 2..].iter().any(|c| (0x40..=0x7e).contains(c)) {
+                buf.len()
+            } else {
+                start
+            }
+        }
+        Some(_) => buf.len(),
+    }
+}
+
+// whether an sgr sequence with the given parameters uses any attributes
+// which vt100 doesn't understand
+fn is_extended(params: &[u8]) -> bool {
+    let mut params = params.split(|&c| c == b';');
+    while let Some(param) = params.next() {
+        let mut subparams = param.split(|&c| c == b':');
+        let has_subparams = param.contains(&b':');
+        match subparams.next().and_then(parse) {
+            Some(2 | 5 | 6 | 8 | 9 | 21 | 53 | 58) => return true,
+            Some(4) if has_subparams => return true,
+            Some(38 | 48) if !has_subparams => {
+                // skip over the parameters giving the color
+                let len = match params.next().and_then(parse) {
+                    Some(5) => 1,
+                    Some(2) => 3,
+                    _ => 0,
+                };
+                for _ in 0..len {
+                    params.next();
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+fn cell_attrs(screen: &vt100::Screen, row: u16, col: u16) -> Attrs {
+    screen.cell(row, col).map_or_else(Attrs::default, |cell| {
+        Attrs::from_colors(cell.fgcolor(), cell.bgcolor())
+    })
+}
+
+fn write_position(buf: &mut Vec<u8>, row: u16, col: u16) {
+    let mut itoa_buf = itoa::Buffer::new();
+    buf.extend(b"\x1b[");
+    buf.extend(itoa_buf.format(row + 1).as_bytes());
+    buf.push(b';');
+    buf.extend(itoa_buf.format(col + 1).as_bytes());
+    buf.push(b'H');
+}
+
+// applies the parameters of an sgr sequence to `attrs`, and returns the
+// parameters which should be passed on to vt100 (or None if there are none
+// left, since an empty sgr sequence would reset everything)
+fn translate_sgr(
+    params: &[u8],
+    mut attrs: Attrs,
+) -> (Attrs, Option<Vec<u8>>) {
+    let params: Vec<&[u8]> = if params.is_empty() {
+        vec![b"0"]
+    } else {
+        params.split(|&c| c == b';').collect()
+    };
+    let mut kept: Vec<&[u8]> = vec![];
+    let mut i = 0;
+    while i < params.len() {
+        let param = params[i];
+        i += 1;
+        let subparams: Vec<Option<u16>> =
+            param.split(|&c| c == b':').map(parse).collect();
+        let has_subparams = subparams.len() > 1;
+        match subparams[0].unwrap_or(0) {
+            0 => {
+                attrs = Attrs::default();
+                kept.push(param);
+            }
+            2 => attrs.set(DIM, true),
+            4 if has_subparams => {
+                let style = subparams[1]
+                    .and_then(|n| u8::try_from(n).ok())
+                    .filter(|&n| n <= 5)
+                    .unwrap_or(0);
+                attrs.set_underline(style);
+                kept.push(if style == 0 { b"24" } else { b"4" });
+            }
+            4 => {
+                attrs.set_underline(1);
+                kept.push(param);
+            }
+            5 | 6 => attrs.set(BLINK, true),
+            8 => attrs.set(HIDDEN, true),
+            9 => attrs.set(STRIKETHROUGH, true),
+            21 => {
+                attrs.set_underline(2);
+                kept.push(b"4");
+            }
+            22 => {
+                attrs.set(DIM, false);
+                kept.push(param);
+            }
+            24 => {
+                attrs.set_underline(0);
+                kept.push(param);
+            }
+            25 => attrs.set(BLINK, false),
+            28 => attrs.set(HIDDEN, false),
+            29 => attrs.set(STRIKETHROUGH, false),
+            53 => attrs.set(OVERLINE, true),
+            55 => attrs.set(OVERLINE, false),
+            58 => {
+                let (color, len) = if has_subparams {
+                    (parse_color(&subparams[1..]), 0)
+                } else {
+                    parse_separate_color(&params[i..])
+                };
+                i += len;
+                if let Some(color) = color {
+                    attrs.underline_color = color;
+                }
+            }
+            59 => attrs.underline_color = vt100::Color::Default,
+            38 | 48 if !has_subparams => {
+                // the color is given by the following parameters, which
+                // shouldn't be interpreted on their own
+                let (_, len) = parse_separate_color(&params[i..]);
+                kept.extend(&params[i - 1..i + len]);
+                i += len;
+            }
+            _ => kept.push(param),
+        }
+    }
+
+    if kept.is_empty() {
+        return (attrs, None);
+    }
+    (attrs, Some(kept.join(&b';')))
+}
+
+// parses the subparameters of a color given as `58:5:n` or `58:2::r:g:b`
+// (where the color space id is optional)
+fn parse_color(subparams: &[Option<u16>]) -> Option<vt100::Color> {
+    let byte = |n: &Option<u16>| n.and_then(|n| u8::try_from(n).ok());
+    match subparams {
+        [Some(5), n] => Some(vt100::Color::Idx(byte(n)?)),
+        [Some(2), _, r, g, b] | [Some(2), r, g, b] => {
+            Some(vt100::Color::Rgb(byte(r)?, byte(g)?, byte(b)?))
+        }
+        _ => None,
+    }
+}
+
+// parses a color given as `58;5;n` or `58;2;r;g;b`, returning the number
+// of parameters it took up
+fn parse_separate_color(params: &[&[u8]]) -> (Option<vt100::Color>, usize) {
+    let len = match params.first().copied().and_then(parse) {
+        Some(5) => 2,
+        Some(2) => 4,
+        _ => return (None, 0),
+    };
+    let len = len.min(params.len());
+    let subparams: Vec<_> = params[..len].iter().map(|p| parse(p)).collect();
+    (parse_color(&subparams), len)
+}
+
+fn parse(param: &[u8]) -> Option<u16> {
+    if param.is_empty() {
+        return Some(0);
+    }
+    std::str::from_utf8(param).ok()?.parse().ok()
+}
@@ -0,0 +1,127 @@
+/// What to do with the region drawn by an inline
+/// [`Output`](crate::blocking::Output) when it is cleaned up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InlineCleanup {
+    /// Leave the final frame in place (so that it ends up in the terminal's
+    /// scrollback), and move the cursor to the line below it.
+    Keep,
+    /// Erase the region, and move the cursor back to where the region
+    /// started.
+    Erase,
+}
+
+// reserves `rows` lines starting at the current line, scrolling the terminal
+// up if there aren't enough lines below the cursor, and leaves the cursor at
+// the start of the first reserved line, with the attributes reset. cursor
+// movement sequences never scroll the terminal, but newlines do, so this is
+// done by writing newlines and then moving back up.
+pub fn init_sequence(rows: u16) -> Vec<u8> {
+    let mut buf = b"\x1b[m\r".to_vec();
+    buf.resize(buf.len() + usize::from(rows.saturating_sub(1)), b'\n');
+    move_up(&mut buf, rows.saturating_sub(1));
+    buf.extend(b"\x1b[J");
+    buf
+}
+
+// `row` is the row of the region that the cursor is currently on
+pub fn deinit_sequence(
+    cleanup: InlineCleanup,
+    row: u16,
+    rows: u16,
+) -> Vec<u8> {
+    let mut buf = vec![];
+    buf.extend(b"\x1b[m");
+    match cleanup {
+        InlineCleanup::Keep => {
+            move_down(&mut buf, rows.saturating_sub(1).saturating_sub(row));
+            buf.extend(b"\r\n");
+        }
+        InlineCleanup::Erase => {
+            move_up(&mut buf, row);
+            buf.extend(b"\r\x1b[J");
+        }
+    }
+    buf.extend(b"\x1b[?25h");
+    buf
+}
+
+// since we don't know which line of the terminal the region starts on,
+// absolute cursor movements (the only ones that vt100 generates are CUP
+// sequences) are replaced by relative movements from the current cursor
+// position. `cur` must reflect the state of the terminal before `buf` is
+// written, and is updated to reflect the state after.
+pub fn translate(buf: &[u8], cur: &mut vt100::Parser) -> Vec<u8> {
+    let mut out = Vec::with_capacity(buf.len());
+    let mut start = 0;
+    let mut i = 0;
+    while i < buf.len() {
+        if buf[i..].starts_with(b"\x1b[") {
+            let params = i + 2;
+            let len = buf[params..]
+                .iter()
+                .position(|c| !matches!(c, b'0'..=b'9' | b';'));
+            if let Some(len) = len {
+                if buf[params + len] == b'H' {
+                    let end = params + len + 1;
+                    cur.process(&buf[start..i]);
+                    out.extend(&buf[start..i]);
+                    let (row, col) = parse_position(&buf[params..end - 1]);
+                    let (cur_row, _) = cur.screen().cursor_position();
+                    out.push(b'\r');
+                    if row < cur_row {
+                        move_up(&mut out, cur_row - row);
+                    } else {
+                        move_down(&mut out, row - cur_row);
+                    }
+                    move_right(&mut out, col);
+                    cur.process(&buf[i..end]);
+                    start = end;
+                    i = end;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+    cur.process(&buf[start..]);
+    out.extend(&buf[start..]);
+    out
+}
+
+// returns the zero-based (row, col) of a CUP sequence's parameters
+fn parse_position(params: &[u8]) -> (u16, u16) {
+    let mut params = params.split(|&c| c == b';').map(|param| {
+        std::str::from_utf8(param)
+            .ok()
+            .and_then(|param| param.parse::<u16>().ok())
+            .unwrap_or(1)
+            .saturating_sub(1)
+    });
+    let row = params.next().unwrap_or(0);
+    let col = params.next().unwrap_or(0);
+    (row, col)
+}
+
+fn move_up(buf: &mut Vec<u8>, n: u16) {
+    move_by(buf, n, b'A');
+}
+
+fn move_down(buf: &mut Vec<u8>, n: u16) {
+    move_by(buf, n, b'B');
+}
+
+fn move_right(buf: &mut Vec<u8>, n: u16) {
+    move_by(buf, n, b'C');
+}
+
+fn move_by(buf: &mut Vec<u8>, n: u16, dir: u8) {
+    if n == 0 {
+        return;
+    }
+    buf.extend(b"\x1b[");
+    if n > 1 {
+        let mut itoa_buf = itoa::Buffer::new();
+        buf.extend(itoa_buf.format(n).as_bytes());
+    }
+    buf.push(dir);
+}
@@ -2,10 +2,15 @@ use tokio::io::AsyncReadExt as _;
 
 use crate::private::Input as _;
 
-/// Switches the terminal on `stdin` to raw mode, and restores it when this
-/// object goes out of scope.
+/// Switches the terminal on `stdin` (or another terminal) to raw mode, and
+/// restores it when this object goes out of scope.
 pub struct RawGuard {
+    // None means stdin, with escape sequences written to stdout
+    tty: Option<std::sync::Arc<std::fs::File>>,
     termios: Option<rustix::termios::Termios>,
+    kitty_keyboard: bool,
+    mouse: Option<crate::MouseMode>,
+    focus_reporting: bool,
 }
 
 impl RawGuard {
@@ -15,50 +20,148 @@ impl RawGuard {
     ///
     /// # Errors
     /// * `Error::SetTerminalMode`: failed to put the terminal into raw mode
+    pub async fn new() -> crate::error::Result<Self> {
+        Self::new_for_tty(None).await
+    }
+
+    /// Switches the given terminal to raw mode and returns a guard object.
+    /// Escape sequences written by this guard (for instance, by
+    /// [`enable_mouse`](RawGuard::enable_mouse)) are written to this
+    /// terminal rather than to `stdout`. This is typically called as part of
+    /// [`Input::from_fd`](Input::from_fd).
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to duplicate the file descriptor
+    /// * `Error::SetTerminalMode`: failed to put the terminal into raw mode
+    pub async fn from_fd(
+        fd: impl std::os::fd::AsFd,
+    ) -> crate::error::Result<Self> {
+        let tty = fd
+            .as_fd()
+            .try_clone_to_owned()
+            .map_err(crate::error::Error::OpenTerminal)?;
+        Self::new_for_tty(Some(std::sync::Arc::new(tty.into()))).await
+    }
+
     // spawn_blocking is uncancellable, and the tcgetattr/tcsetattr calls
     // can't panic, so unwrap is safe here
     #[allow(clippy::missing_panics_doc)]
-    pub async fn new() -> crate::error::Result<Self> {
-        let termios = tokio::task::spawn_blocking(move || {
-            rustix::termios::tcgetattr(std::io::stdin())
-                .map_err(crate::error::Error::SetTerminalMode)
-        })
-        .await
-        .unwrap()?;
-        let mut termios_raw = termios.clone();
-        termios_raw.make_raw();
-        tokio::task::spawn_blocking(move || {
-            rustix::termios::tcsetattr(
-                std::io::stdin(),
-                rustix::termios::OptionalActions::Now,
-                &termios_raw,
-            )
-            .map_err(crate::error::Error::SetTerminalMode)
-        })
-        .await
-        .unwrap()?;
+    async fn new_for_tty(
+        tty: Option<std::sync::Arc<std::fs::File>>,
+    ) -> crate::error::Result<Self> {
+        let termios = {
+            let tty = tty.clone();
+            tokio::task::spawn_blocking(move || {
+                tty.map_or_else(
+                    || crate::blocking::input::make_raw(std::io::stdin()),
+                    |tty| crate::blocking::input::make_raw(&*tty),
+                )
+            })
+            .await
+            .unwrap()?
+        };
         Ok(Self {
+            tty,
             termios: Some(termios),
+            kitty_keyboard: false,
+            mouse: None,
+            focus_reporting: false,
         })
     }
 
+    /// Enables the kitty keyboard protocol with the given flags, by writing
+    /// the appropriate escape sequence to the terminal. The protocol will be
+    /// disabled again when this guard is cleaned up. Calling this again
+    /// replaces the previously enabled flags.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub async fn enable_kitty_keyboard(
+        &mut self,
+        flags: crate::KittyFlags,
+    ) -> crate::error::Result<()> {
+        let mut buf = vec![];
+        if self.kitty_keyboard {
+            buf.extend(crate::KITTY_KEYBOARD_POP);
+        }
+        buf.extend(flags.push_sequence());
+        crate::output::write_terminal(self.tty.as_ref(), &buf).await?;
+        self.kitty_keyboard = true;
+        Ok(())
+    }
+
+    /// Enables mouse reporting in the given mode, by writing the
+    /// appropriate escape sequence to the terminal. Mouse events are
+    /// reported using SGR encoding. Mouse reporting will be disabled again
+    /// when this guard is cleaned up. Calling this again replaces the
+    /// previously enabled mode.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub async fn enable_mouse(
+        &mut self,
+        mode: crate::MouseMode,
+    ) -> crate::error::Result<()> {
+        let mut buf = vec![];
+        if let Some(mode) = self.mouse {
+            buf.extend(mode.disable_sequence());
+        }
+        buf.extend(mode.enable_sequence());
+        crate::output::write_terminal(self.tty.as_ref(), &buf).await?;
+        self.mouse = Some(mode);
+        Ok(())
+    }
+
+    /// Enables focus reporting, by writing the appropriate escape sequence
+    /// to the terminal. Focus reporting will be disabled again when this
+    /// guard is cleaned up.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub async fn enable_focus_reporting(
+        &mut self,
+    ) -> crate::error::Result<()> {
+        if !self.focus_reporting {
+            crate::output::write_terminal(
+                self.tty.as_ref(),
+                crate::FOCUS_REPORTING_ENABLE,
+            )
+            .await?;
+            self.focus_reporting = true;
+        }
+        Ok(())
+    }
+
     /// Switch back from raw mode early.
     ///
     /// # Errors
+    /// * `Error::WriteStdout`: failed to disable the kitty keyboard
+    ///   protocol, mouse reporting, or focus reporting
     /// * `Error::SetTerminalMode`: failed to return the terminal from raw
     ///   mode
     // spawn_blocking is uncancellable, and the tcsetattr call can't panic, so
     // unwrap is safe here
     #[allow(clippy::missing_panics_doc)]
     pub async fn cleanup(&mut self) -> crate::error::Result<()> {
+        let deinit = self.deinit_sequence();
+        if !deinit.is_empty() {
+            crate::output::write_terminal(self.tty.as_ref(), &deinit).await?;
+        }
         if let Some(termios) = self.termios.take() {
+            let tty = self.tty.clone();
             tokio::task::spawn_blocking(move || {
-                rustix::termios::tcsetattr(
-                    std::io::stdin(),
-                    rustix::termios::OptionalActions::Now,
-                    &termios,
+                tty.map_or_else(
+                    || {
+                        crate::blocking::input::restore(
+                            std::io::stdin(),
+                            &termios,
+                        )
+                    },
+                    |tty| crate::blocking::input::restore(&*tty, &termios),
                 )
-                .map_err(crate::error::Error::SetTerminalMode)
             })
             .await
             .unwrap()
@@ -66,6 +169,22 @@ impl RawGuard {
             Ok(())
         }
     }
+
+    fn deinit_sequence(&mut self) -> Vec<u8> {
+        let mut buf = vec![];
+        if let Some(mode) = self.mouse.take() {
+            buf.extend(mode.disable_sequence());
+        }
+        if self.focus_reporting {
+            self.focus_reporting = false;
+            buf.extend(crate::FOCUS_REPORTING_DISABLE);
+        }
+        if self.kitty_keyboard {
+            self.kitty_keyboard = false;
+            buf.extend(crate::KITTY_KEYBOARD_POP);
+        }
+        buf
+    }
 }
 
 impl Drop for RawGuard {
@@ -80,32 +199,56 @@ impl Drop for RawGuard {
         // block_in_place/block_on doesn't work on the current_thread runtime,
         // but should be kept in sync with the actual things that `cleanup`
         // does.
+        use std::io::Write as _;
+
+        let deinit = self.deinit_sequence();
+        if !deinit.is_empty() {
+            if let Some(tty) = &self.tty {
+                let _ = crate::blocking::output::write_tty(tty, &deinit);
+            } else {
+                let mut stdout = std::io::stdout();
+                let _ = stdout.write_all(&deinit);
+                let _ = stdout.flush();
+            }
+        }
         if let Some(termios) = self.termios.take() {
-            let _ = rustix::termios::tcsetattr(
-                std::io::stdin(),
-                rustix::termios::OptionalActions::Now,
-                &termios,
+            let _ = self.tty.as_ref().map_or_else(
+                || {
+                    crate::blocking::input::restore(
+                        std::io::stdin(),
+                        &termios,
+                    )
+                },
+                |tty| crate::blocking::input::restore(&**tty, &termios),
             );
         }
     }
 }
 
-/// Manages handling terminal input from `stdin`.
+/// Manages handling terminal input from `stdin` (or another terminal or
+/// reader).
 ///
 /// The primary interface provided is [`read_key`](Input::read_key). You can
 /// additionally configure the types of keypresses you are interested in
 /// through the `parse_*` methods. This configuration can be changed between
 /// any two calls to [`read_key`](Input::read_key).
 ///
+/// Terminals are read through tokio's reactor rather than on a blocking
+/// thread, so pending calls to [`read_key`](Input::read_key) can be
+/// cancelled at any time, and don't prevent the tokio runtime from shutting
+/// down.
+///
 /// # Note
 ///
-/// This is built on [`tokio::io::Stdin`], and inherits its caveats. In
-/// particular, it will likely cause a hang until one more newline is received
-/// when the tokio runtime shuts down. Because of this, it is generally
-/// recommended to spawn a thread and use
-/// [`textmode::blocking::Input`](crate::blocking::Input) instead.
+/// If `stdin` is not something that can be polled (for instance, if it is
+/// redirected from a regular file), [`new`](Input::new) and
+/// [`new_without_raw`](Input::new_without_raw) fall back to reading through
+/// [`tokio::io::Stdin`], which inherits its caveats. In particular, reads
+/// from it run on a blocking thread, which the tokio runtime will wait for
+/// when shutting down.
 pub struct Input {
-    stdin: tokio::io::Stdin,
+    reader: Box<dyn tokio::io::AsyncRead + Unpin + Send>,
+    tty: Option<std::sync::Arc<std::fs::File>>,
     raw: Option<RawGuard>,
 
     buf: Vec<u8>,
@@ -116,6 +259,9 @@ pub struct Input {
     parse_meta: bool,
     parse_special_keys: bool,
     parse_single: bool,
+    escape_timeout: std::time::Duration,
+    escape_deadline: Option<tokio::time::Instant>,
+    terminfo_keys: Option<crate::TerminfoKeys>,
 }
 
 impl crate::private::Input for Input {
@@ -162,6 +308,10 @@ impl crate::private::Input for Input {
     fn should_parse_special_keys(&self) -> bool {
         self.parse_special_keys
     }
+
+    fn terminfo_keys(&self) -> Option<&crate::TerminfoKeys> {
+        self.terminfo_keys.as_ref()
+    }
 }
 
 impl Input {
@@ -176,12 +326,75 @@ impl Input {
         Ok(self_)
     }
 
+    /// Creates a new `Input` instance which reads from the given terminal
+    /// instead of `stdin`, containing a [`RawGuard`](RawGuard) instance for
+    /// that terminal. Queries such as
+    /// [`query_cursor_position`](Input::query_cursor_position) are also
+    /// written to this terminal rather than to `stdout`.
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to duplicate the file descriptor, or
+    ///   to register it with the tokio reactor
+    /// * `Error::SetTerminalMode`: failed to put the terminal into raw mode
+    pub async fn from_fd(
+        fd: impl std::os::fd::AsFd,
+    ) -> crate::error::Result<Self> {
+        let tty: std::fs::File = fd
+            .as_fd()
+            .try_clone_to_owned()
+            .map_err(crate::error::Error::OpenTerminal)?
+            .into();
+        let reader =
+            FdReader::new(&tty).map_err(crate::error::Error::OpenTerminal)?;
+        let tty = std::sync::Arc::new(tty);
+        let mut self_ = Self::new_from_reader(Box::new(reader));
+        self_.raw = Some(RawGuard::new_for_tty(Some(tty.clone())).await?);
+        self_.tty = Some(tty);
+        Ok(self_)
+    }
+
+    /// Creates a new `Input` instance which reads from the controlling
+    /// terminal (`/dev/tty`) instead of `stdin`. This allows reading
+    /// keypresses even when `stdin` is redirected, for instance when data
+    /// is being piped into the program. See [`from_fd`](Input::from_fd).
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to open `/dev/tty`
+    /// * `Error::SetTerminalMode`: failed to put the terminal into raw mode
+    pub async fn from_tty() -> crate::error::Result<Self> {
+        Self::from_fd(crate::blocking::output::open_tty()?).await
+    }
+
+    /// Creates a new `Input` instance which parses keypresses from the data
+    /// produced by the given reader, without creating a
+    /// [`RawGuard`](RawGuard) instance.
+    #[must_use]
+    pub fn from_reader(
+        reader: impl tokio::io::AsyncRead + Unpin + Send + 'static,
+    ) -> Self {
+        Self::new_from_reader(Box::new(reader))
+    }
+
     /// Creates a new `Input` instance without creating a
     /// [`RawGuard`](RawGuard) instance.
     #[must_use]
     pub fn new_without_raw() -> Self {
+        // AsyncFd panics if there is no runtime to register with
+        let reader = tokio::runtime::Handle::try_current()
+            .ok()
+            .and_then(|_| FdReader::new(std::io::stdin()).ok());
+        reader.map_or_else(
+            || Self::new_from_reader(Box::new(tokio::io::stdin())),
+            |reader| Self::new_from_reader(Box::new(reader)),
+        )
+    }
+
+    fn new_from_reader(
+        reader: Box<dyn tokio::io::AsyncRead + Unpin + Send>,
+    ) -> Self {
         Self {
-            stdin: tokio::io::stdin(),
+            reader,
+            tty: None,
             raw: None,
             buf: Vec::with_capacity(4096),
             pos: 0,
@@ -190,6 +403,9 @@ impl Input {
             parse_meta: true,
             parse_special_keys: true,
             parse_single: true,
+            escape_timeout: std::time::Duration::ZERO,
+            escape_deadline: None,
+            terminfo_keys: None,
         }
     }
 
@@ -200,6 +416,65 @@ impl Input {
         self.raw.take()
     }
 
+    /// Enables the kitty keyboard protocol with the given flags, which
+    /// allows `read_key` to report keys that would otherwise be ambiguous
+    /// (as well as key releases and repeats, if requested) as
+    /// [`Event`](crate::Key::Event) keys. This is done through the
+    /// [`RawGuard`](RawGuard) instance stored in this `Input` instance, and
+    /// so does nothing if there isn't one; in that case, call
+    /// [`RawGuard::enable_kitty_keyboard`] directly instead.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub async fn enable_kitty_keyboard(
+        &mut self,
+        flags: crate::KittyFlags,
+    ) -> crate::error::Result<()> {
+        if let Some(raw) = &mut self.raw {
+            raw.enable_kitty_keyboard(flags).await?;
+        }
+        Ok(())
+    }
+
+    /// Enables mouse reporting in the given mode, which allows `read_key` to
+    /// report [`Mouse`](crate::Key::Mouse) keys. This is done through the
+    /// [`RawGuard`](RawGuard) instance stored in this `Input` instance, and
+    /// so does nothing if there isn't one; in that case, call
+    /// [`RawGuard::enable_mouse`] directly instead.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub async fn enable_mouse(
+        &mut self,
+        mode: crate::MouseMode,
+    ) -> crate::error::Result<()> {
+        if let Some(raw) = &mut self.raw {
+            raw.enable_mouse(mode).await?;
+        }
+        Ok(())
+    }
+
+    /// Enables focus reporting, which allows `read_key` to report
+    /// [`FocusGained`](crate::Key::FocusGained) and
+    /// [`FocusLost`](crate::Key::FocusLost) keys. This is done through the
+    /// [`RawGuard`](RawGuard) instance stored in this `Input` instance, and
+    /// so does nothing if there isn't one; in that case, call
+    /// [`RawGuard::enable_focus_reporting`] directly instead.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to the
+    ///   terminal
+    pub async fn enable_focus_reporting(
+        &mut self,
+    ) -> crate::error::Result<()> {
+        if let Some(raw) = &mut self.raw {
+            raw.enable_focus_reporting().await?;
+        }
+        Ok(())
+    }
+
     /// Sets whether `read_key` should try to produce
     /// [`String`](crate::Key::String) or [`Char`](crate::Key::Char) keys when
     /// possible, rather than [`Bytes`](crate::Key::Bytes) or
@@ -246,9 +521,96 @@ impl Input {
         self.parse_single = parse;
     }
 
+    /// Sets how long `read_key` should wait for the rest of an escape
+    /// sequence to arrive after receiving a partial one (for instance, a lone
+    /// `ESC` byte). If no more bytes arrive within this time, the bytes that
+    /// were received will be parsed on their own (so a lone `ESC` byte will
+    /// produce [`Escape`](crate::Key::Escape)). This is similar to the
+    /// `ESCDELAY` setting in ncurses, and can be useful over slow
+    /// connections where escape sequences may be split across multiple
+    /// reads. Defaults to zero, meaning to not wait at all.
+    pub fn escape_timeout(&mut self, timeout: std::time::Duration) {
+        self.escape_timeout = timeout;
+    }
+
+    /// Sets a table of additional key sequences for `read_key` to
+    /// recognize, typically loaded from the terminfo entry for the current
+    /// terminal with [`TerminfoKeys::from_env`](crate::TerminfoKeys::from_env).
+    /// Sequences in this table take precedence over the built-in parsing,
+    /// which is still used for any sequences not in the table. Only has an
+    /// effect when `parse_special_keys` is enabled. Defaults to `None`.
+    pub fn terminfo_keys(&mut self, keys: Option<crate::TerminfoKeys>) {
+        self.terminfo_keys = keys;
+    }
+
+    /// Asks the terminal for the current position of the cursor, by
+    /// writing `ESC[6n` to the terminal that `output` draws to and waiting
+    /// up to `timeout` for the reply to arrive on this input's terminal.
+    /// Returns the position as `(row, col)` (zero-based, as used by
+    /// [`move_to`](crate::Textmode::move_to)), or `None` if no reply arrived
+    /// in time. Any other input received while waiting is kept, and will be
+    /// returned by later calls to `read_key`.
+    ///
+    /// Note that the reply uses the same format as F3 pressed with
+    /// modifiers (`ESC[1;2R` is both shift+F3 and a reply of row 1, column
+    /// 2), so such a keypress received while waiting will be treated as the
+    /// reply.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the query to the terminal
+    /// * `Error::ReadStdin`: failed to read data from stdin
+    pub async fn query_cursor_position(
+        &mut self,
+        output: &mut crate::Output,
+        timeout: std::time::Duration,
+    ) -> crate::error::Result<Option<(u16, u16)>> {
+        let Some(params) =
+            self.query(output, b"\x1b[6n", b'R', 2, timeout).await?
+        else {
+            return Ok(None);
+        };
+        let row =
+            u16::try_from(params[0].saturating_sub(1)).unwrap_or(u16::MAX);
+        let col =
+            u16::try_from(params[1].saturating_sub(1)).unwrap_or(u16::MAX);
+        Ok(Some((row, col)))
+    }
+
+    /// Asks the terminal for its status, by writing `ESC[5n` to the
+    /// terminal that `output` draws to and waiting up to `timeout` for the
+    /// reply to arrive on this input's terminal. Returns `Some(true)` if the
+    /// terminal reports that it is working correctly, `Some(false)` if it
+    /// reports a malfunction, or `None` if no reply arrived in time. Any
+    /// other input received while waiting is kept, and will be returned by
+    /// later calls to `read_key`.
+    ///
+    /// Since every terminal replies to this query, this can also be used
+    /// to check whether the terminal is responding at all.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the query to the terminal
+    /// * `Error::ReadStdin`: failed to read data from stdin
+    pub async fn query_device_status(
+        &mut self,
+        output: &mut crate::Output,
+        timeout: std::time::Duration,
+    ) -> crate::error::Result<Option<bool>> {
+        Ok(self
+            .query(output, b"\x1b[5n", b'n', 1, timeout)
+            .await?
+            .map(|params| params[0] == 0))
+    }
+
     /// Reads a keypress from the terminal on `stdin`. Returns `Ok(None)` on
     /// EOF.
     ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. If it is used as an event in a
+    /// [`tokio::select!`] statement and some other branch completes first,
+    /// any partial keypress which was read is kept, and will be completed by
+    /// the next call.
+    ///
     /// # Errors
     /// * `Error::ReadStdin`: failed to read data from stdin
     pub async fn read_key(
@@ -275,46 +637,313 @@ impl Input {
         }
     }
 
+    /// Reads keypresses from the terminal on `stdin` until they match a
+    /// binding in `keymap` or fail to match any binding, and returns the
+    /// result (which will never be [`Pending`](crate::KeymapEvent::Pending)).
+    /// If the keymap has a timeout set, a partially entered sequence is
+    /// ended once no key has been received for that long. Returns
+    /// `Ok(None)` on EOF, after returning any keys which were still
+    /// pending.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. Keys which were already fed into
+    /// `keymap` stay pending there, and any partial keypress which was read
+    /// is kept, and will be completed by the next call.
+    ///
+    /// # Errors
+    /// * `Error::ReadStdin`: failed to read data from stdin
+    pub async fn read_binding<T: Clone>(
+        &mut self,
+        keymap: &mut crate::Keymap<T>,
+    ) -> crate::error::Result<Option<crate::KeymapEvent<T>>> {
+        loop {
+            if let Some(event) = keymap.next_event() {
+                return Ok(Some(event));
+            }
+
+            if let Some(deadline) = keymap.deadline() {
+                if !self
+                    .wait_for_input(tokio::time::Instant::from_std(deadline))
+                    .await?
+                {
+                    if let Some(event) = keymap.flush() {
+                        return Ok(Some(event));
+                    }
+                }
+            }
+
+            let Some(key) = self.read_key().await? else {
+                return Ok(keymap.flush());
+            };
+            match keymap.feed(key) {
+                crate::KeymapEvent::Pending => {}
+                event => return Ok(Some(event)),
+            }
+        }
+    }
+
+    // returns false if no data arrived before the deadline
+    async fn wait_for_input(
+        &mut self,
+        deadline: tokio::time::Instant,
+    ) -> crate::error::Result<bool> {
+        if !self.buf_is_empty() {
+            return Ok(true);
+        }
+        self.buf.clear();
+        self.pos = 0;
+        // on EOF, the buffer is left empty so that read_key will try again
+        // and report it
+        Ok(tokio::time::timeout_at(deadline, self.read_more())
+            .await
+            .ok()
+            .transpose()?
+            .is_some())
+    }
+
+    // every await point in here must leave the buffer in a consistent state
+    // if the future is dropped there, so that read_key is cancel safe. all
+    // modifications to the buffer happen in read_more after its await has
+    // completed, and the state which needs to persist across calls (such as
+    // the escape timeout deadline) is stored in self.
     async fn fill_buf(&mut self) -> crate::error::Result<()> {
         if self.buf_is_empty() {
-            self.buf.resize(4096, 0);
+            self.buf.clear();
             self.pos = 0;
-            let bytes = read_stdin(&mut self.stdin, &mut self.buf).await?;
-            if bytes == 0 {
+            if self.read_more().await? == 0 {
                 return Ok(());
             }
-            self.buf.truncate(bytes);
         }
 
         if self.parse_utf8 {
             let expected_bytes =
                 self.expected_leading_utf8_bytes(self.buf()[0]);
-            if self.buf.len() < self.pos + expected_bytes {
-                let mut cur = self.buf.len();
-                self.buf.resize(4096 + expected_bytes, 0);
-                while cur < self.pos + expected_bytes {
-                    let bytes =
-                        read_stdin(&mut self.stdin, &mut self.buf[cur..])
-                            .await?;
-                    if bytes == 0 {
-                        return Ok(());
-                    }
-                    cur += bytes;
+            while self.buf.len() < self.pos + expected_bytes {
+                if self.read_more().await? == 0 {
+                    return Ok(());
+                }
+            }
+        }
+
+        if !self.escape_timeout.is_zero() {
+            while self.escape_sequence_is_incomplete() {
+                // the escape timeout is measured from when the partial
+                // sequence first arrived, even if an earlier call was
+                // cancelled while waiting for the rest of it
+                let deadline =
+                    *self.escape_deadline.get_or_insert_with(|| {
+                        tokio::time::Instant::now() + self.escape_timeout
+                    });
+                let Ok(res) =
+                    tokio::time::timeout_at(deadline, self.read_more()).await
+                else {
+                    break;
+                };
+                if res? == 0 {
+                    break;
                 }
-                self.buf.truncate(cur);
+            }
+            self.escape_deadline = None;
+        }
+
+        while self.paste_is_incomplete() {
+            if self.read_more().await? == 0 {
+                return Ok(());
             }
         }
 
         Ok(())
     }
+
+    // appends more data to the buffer. this is cancel safe, since the buffer
+    // is only modified once the read has completed.
+    async fn read_more(&mut self) -> crate::error::Result<usize> {
+        self.buf.reserve(4096);
+        self.reader
+            .read_buf(&mut self.buf)
+            .await
+            .map_err(crate::error::Error::ReadStdin)
+    }
+
+    async fn query(
+        &mut self,
+        output: &mut crate::Output,
+        request: &[u8],
+        final_byte: u8,
+        nparams: usize,
+        timeout: std::time::Duration,
+    ) -> crate::error::Result<Option<Vec<u32>>> {
+        output.write_raw(request).await?;
+        let deadline = tokio::time::Instant::now() + timeout;
+        if self.buf_is_empty() {
+            self.buf.clear();
+            self.pos = 0;
+        }
+        loop {
+            if let Some(params) = self.take_csi_reply(final_byte, nparams) {
+                return Ok(Some(params));
+            }
+            let Ok(res) =
+                tokio::time::timeout_at(deadline, self.read_more()).await
+            else {
+                return Ok(None);
+            };
+            if res? == 0 {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/// A [`Stream`](futures_core::Stream) of keypresses, created by
+/// [`Input::into_stream`].
+///
+/// This owns the [`Input`] instance (and so also its
+/// [`RawGuard`](RawGuard), if it has one), and yields the same keys that
+/// [`read_key`](Input::read_key) would return. The stream ends when
+/// `read_key` would return `Ok(None)`. Like `read_key`, polling the stream
+/// can be safely abandoned at any point (for instance, when another branch
+/// of a `tokio::select!` completes first) without losing input, since any
+/// in progress read is kept inside the stream until it is polled again.
+pub struct InputStream {
+    state: StreamState,
+}
+
+enum StreamState {
+    Idle(Box<Input>),
+    Reading(
+        std::pin::Pin<
+            Box<
+                dyn std::future::Future<
+                        Output = (
+                            Box<Input>,
+                            crate::error::Result<Option<crate::Key>>,
+                        ),
+                    > + Send,
+            >,
+        >,
+    ),
+    Finished(Box<Input>),
+    Empty,
+}
+
+impl Input {
+    /// Converts this `Input` into a [`Stream`](futures_core::Stream) of
+    /// keypresses. See [`InputStream`].
+    #[must_use]
+    pub fn into_stream(self) -> InputStream {
+        InputStream {
+            state: StreamState::Idle(Box::new(self)),
+        }
+    }
+}
+
+impl InputStream {
+    /// Returns the [`Input`] instance back out of the stream. Returns
+    /// `None` if the stream was in the middle of reading a key (that is, it
+    /// was polled and returned `Pending` without being polled to completion
+    /// afterwards), since the input is in use by that read.
+    #[must_use]
+    pub fn into_inner(self) -> Option<Input> {
+        match self.state {
+            StreamState::Idle(input) | StreamState::Finished(input) => {
+                Some(*input)
+            }
+            StreamState::Reading(_) | StreamState::Empty => None,
+        }
+    }
+}
+
+impl futures_core::Stream for InputStream {
+    type Item = crate::error::Result<crate::Key>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let mut fut =
+            match std::mem::replace(&mut self.state, StreamState::Empty) {
+                StreamState::Idle(mut input) => Box::pin(async move {
+                    let res = input.read_key().await;
+                    (input, res)
+                }),
+                StreamState::Reading(fut) => fut,
+                state @ (StreamState::Finished(_) | StreamState::Empty) => {
+                    self.state = state;
+                    return std::task::Poll::Ready(None);
+                }
+            };
+        let std::task::Poll::Ready((input, res)) = fut.as_mut().poll(cx)
+        else {
+            self.state = StreamState::Reading(fut);
+            return std::task::Poll::Pending;
+        };
+        if let Some(res) = res.transpose() {
+            self.state = StreamState::Idle(input);
+            std::task::Poll::Ready(Some(res))
+        } else {
+            // keep the input around, so that the terminal isn't restored
+            // until the stream is dropped
+            self.state = StreamState::Finished(input);
+            std::task::Poll::Ready(None)
+        }
+    }
+}
+
+// reads from a file descriptor through the tokio reactor. the file
+// descriptor is left in blocking mode, since the underlying file description
+// is typically shared with stdout (and making writes to stdout non-blocking
+// would break other code writing to it), so we check that data is actually
+// available before each read.
+struct FdReader {
+    fd: tokio::io::unix::AsyncFd<std::os::fd::OwnedFd>,
 }
 
-async fn read_stdin(
-    stdin: &mut tokio::io::Stdin,
-    buf: &mut [u8],
-) -> crate::error::Result<usize> {
-    stdin
-        .read(buf)
-        .await
-        .map_err(crate::error::Error::ReadStdin)
+impl FdReader {
+    fn new(fd: impl std::os::fd::AsFd) -> std::io::Result<Self> {
+        let fd = fd.as_fd().try_clone_to_owned()?;
+        Ok(Self {
+            fd: tokio::io::unix::AsyncFd::with_interest(
+                fd,
+                tokio::io::Interest::READABLE,
+            )?,
+        })
+    }
+}
+
+impl tokio::io::AsyncRead for FdReader {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        loop {
+            let mut guard = std::task::ready!(self.fd.poll_read_ready(cx))?;
+            let mut fds = [rustix::event::PollFd::new(
+                guard.get_inner(),
+                rustix::event::PollFlags::IN,
+            )];
+            match rustix::event::poll(&mut fds, 0) {
+                Ok(0) => {
+                    guard.clear_ready();
+                    continue;
+                }
+                Ok(_) => {}
+                Err(rustix::io::Errno::INTR) => continue,
+                Err(e) => return std::task::Poll::Ready(Err(e.into())),
+            }
+            match rustix::io::read(
+                guard.get_inner(),
+                buf.initialize_unfilled(),
+            ) {
+                Ok(bytes) => {
+                    buf.advance(bytes);
+                    return std::task::Poll::Ready(Ok(()));
+                }
+                Err(rustix::io::Errno::INTR) => {}
+                Err(e) => return std::task::Poll::Ready(Err(e.into())),
+            }
+        }
+    }
 }
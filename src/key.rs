@@ -25,9 +25,250 @@ pub enum Key {
     Delete,
     PageUp,
     PageDown,
+    /// Shift+Tab.
+    BackTab,
     /// The associated value will be the number corresponding to the `F` key.
     /// For instance, `F1` will be `F(1)`.
     F(u8),
+    /// A special key pressed while holding down one or more modifier keys.
+    /// For instance, `Ctrl+Up` will be
+    /// `Modified(Box::new(Key::Up), Modifiers::CTRL)`. Only the cursor keys,
+    /// [`Home`](Key::Home), [`End`](Key::End), [`Insert`](Key::Insert),
+    /// [`Delete`](Key::Delete), [`PageUp`](Key::PageUp),
+    /// [`PageDown`](Key::PageDown), and [`F`](Key::F) keys are reported this
+    /// way.
+    Modified(Box<Self>, Modifiers),
+    /// A key event reported via the kitty keyboard protocol. These are only
+    /// produced after enabling the protocol with
+    /// [`enable_kitty_keyboard`](crate::blocking::Input::enable_kitty_keyboard)
+    /// (or the equivalent method on the async `Input`). Keys which have no
+    /// equivalent here, such as media keys, are reported as
+    /// [`Bytes`](Key::Bytes) containing the sequence the terminal sent.
+    Event(Box<KeyEvent>),
+    /// A mouse event. These are only produced after enabling mouse
+    /// reporting with
+    /// [`enable_mouse`](crate::blocking::Input::enable_mouse) (or the
+    /// equivalent method on the async `Input`).
+    Mouse(crate::MouseEvent),
+    /// Text pasted into the terminal. These are only produced after enabling
+    /// bracketed paste mode with
+    /// [`enable_bracketed_paste`](crate::blocking::Output::enable_bracketed_paste)
+    /// (or the equivalent method on the async `Output`). Any invalid UTF-8
+    /// in the pasted text will be replaced with `U+FFFD`.
+    Paste(String),
+    /// The terminal gained focus. These are only produced after enabling
+    /// focus reporting with
+    /// [`enable_focus_reporting`](crate::blocking::Input::enable_focus_reporting)
+    /// (or the equivalent method on the async `Input`).
+    FocusGained,
+    /// The terminal lost focus. These are only produced after enabling focus
+    /// reporting with
+    /// [`enable_focus_reporting`](crate::blocking::Input::enable_focus_reporting)
+    /// (or the equivalent method on the async `Input`).
+    FocusLost,
+}
+
+/// A key event reported via the kitty keyboard protocol.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct KeyEvent {
+    /// The key that was pressed. Keys which produce text will be reported
+    /// as [`Char`](Key::Char) with the unshifted character, with the
+    /// modifiers stored separately (so `Ctrl+I` will be `Char('i')` with
+    /// [`Modifiers::CTRL`], while the tab key will be `Ctrl(b'i')`).
+    pub key: Key,
+    /// The modifier keys held down during the event.
+    pub modifiers: Modifiers,
+    /// Whether this event was a press, repeat, or release.
+    pub kind: KeyEventKind,
+}
+
+/// The type of a [`KeyEvent`].
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum KeyEventKind {
+    /// The key was pressed.
+    Press,
+    /// The key was held down long enough to repeat.
+    Repeat,
+    /// The key was released.
+    Release,
+}
+
+impl KeyEventKind {
+    fn from_param(param: u32) -> Option<Self> {
+        match param {
+            1 => Some(Self::Press),
+            2 => Some(Self::Repeat),
+            3 => Some(Self::Release),
+            _ => None,
+        }
+    }
+
+    fn param(self) -> u8 {
+        match self {
+            Self::Press => 1,
+            Self::Repeat => 2,
+            Self::Release => 3,
+        }
+    }
+}
+
+/// Set of modifier keys held down during a keypress.
+///
+/// The [`HYPER`](Modifiers::HYPER), [`META`](Modifiers::META),
+/// [`CAPS_LOCK`](Modifiers::CAPS_LOCK), and
+/// [`NUM_LOCK`](Modifiers::NUM_LOCK) modifiers are only reported by the kitty
+/// keyboard protocol.
+#[derive(Eq, PartialEq, Clone, Copy, Default, Hash)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    /// No modifiers.
+    pub const NONE: Self = Self(0);
+    /// The shift key.
+    pub const SHIFT: Self = Self(1);
+    /// The alt (or meta) key.
+    pub const ALT: Self = Self(2);
+    /// The control key.
+    pub const CTRL: Self = Self(4);
+    /// The super (or windows, or command) key.
+    pub const SUPER: Self = Self(8);
+    /// The hyper key.
+    pub const HYPER: Self = Self(16);
+    /// The meta key, when it is distinct from the alt key.
+    pub const META: Self = Self(32);
+    /// Caps lock is active.
+    pub const CAPS_LOCK: Self = Self(64);
+    /// Num lock is active.
+    pub const NUM_LOCK: Self = Self(128);
+
+    const NAMES: &'static [(Self, &'static str)] = &[
+        (Self::SHIFT, "SHIFT"),
+        (Self::ALT, "ALT"),
+        (Self::CTRL, "CTRL"),
+        (Self::SUPER, "SUPER"),
+        (Self::HYPER, "HYPER"),
+        (Self::META, "META"),
+        (Self::CAPS_LOCK, "CAPS_LOCK"),
+        (Self::NUM_LOCK, "NUM_LOCK"),
+    ];
+
+    /// Returns true if no modifiers are set.
+    #[must_use]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if all of the modifiers in `other` are also set in
+    /// `self`.
+    #[must_use]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns true if the shift key is set.
+    #[must_use]
+    pub fn shift(self) -> bool {
+        self.contains(Self::SHIFT)
+    }
+
+    /// Returns true if the alt key is set.
+    #[must_use]
+    pub fn alt(self) -> bool {
+        self.contains(Self::ALT)
+    }
+
+    /// Returns true if the control key is set.
+    #[must_use]
+    pub fn ctrl(self) -> bool {
+        self.contains(Self::CTRL)
+    }
+
+    /// Returns true if the super key is set.
+    #[must_use]
+    pub fn super_(self) -> bool {
+        self.contains(Self::SUPER)
+    }
+
+    // xterm encodes modifiers as a parameter value of 1 plus the bitmask
+    pub(crate) fn from_xterm_param(param: u32) -> Option<Self> {
+        let bits = param.checked_sub(1)?;
+        u8::try_from(bits).ok().map(Self)
+    }
+
+    pub(crate) fn xterm_param(self) -> u32 {
+        u32::from(self.0) + 1
+    }
+}
+
+impl std::ops::BitOr for Modifiers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl std::fmt::Debug for Modifiers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Modifiers(")?;
+        if self.is_empty() {
+            write!(f, "NONE")?;
+        }
+        let mut first = true;
+        for &(modifier, name) in Self::NAMES {
+            if self.contains(modifier) {
+                if !first {
+                    write!(f, " | ")?;
+                }
+                write!(f, "{name}")?;
+                first = false;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+/// Progressive enhancement flags for the kitty keyboard protocol. See
+/// <https://sw.kovidgoyal.net/kitty/keyboard-protocol/> for details about
+/// what each flag does.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, Hash)]
+pub struct KittyFlags(u8);
+
+impl KittyFlags {
+    /// Report keys that would otherwise be ambiguous (such as `Escape`,
+    /// `Ctrl+I`, or `Alt+[`) using `CSI u` sequences.
+    pub const DISAMBIGUATE_ESCAPE_CODES: Self = Self(1);
+    /// Report key repeat and release events in addition to presses.
+    pub const REPORT_EVENT_TYPES: Self = Self(2);
+    /// Report the shifted and base layout keys along with the key itself.
+    pub const REPORT_ALTERNATE_KEYS: Self = Self(4);
+    /// Report all keys (including text keys, `Enter`, `Tab`, and
+    /// `Backspace`) using escape sequences.
+    pub const REPORT_ALL_KEYS_AS_ESCAPE_CODES: Self = Self(8);
+    /// Report the text generated by the key along with the key itself.
+    pub const REPORT_ASSOCIATED_TEXT: Self = Self(16);
+
+    pub(crate) fn push_sequence(self) -> Vec<u8> {
+        let mut itoa_buf = itoa::Buffer::new();
+        let mut bytes = b"\x1b[>".to_vec();
+        bytes.extend(itoa_buf.format(self.0).as_bytes());
+        bytes.push(b'u');
+        bytes
+    }
+}
+
+impl std::ops::BitOr for KittyFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
 }
 
 impl Key {
@@ -57,6 +298,7 @@ impl Key {
             Self::Delete => b"\x1b[3~".to_vec(),
             Self::PageUp => b"\x1b[5~".to_vec(),
             Self::PageDown => b"\x1b[6~".to_vec(),
+            Self::BackTab => b"\x1b[Z".to_vec(),
             Self::F(c) => match c {
                 1 => b"\x1bOP".to_vec(),
                 2 => b"\x1bOQ".to_vec(),
@@ -80,6 +322,178 @@ impl Key {
                 20 => b"\x1b[34~".to_vec(),
                 _ => vec![],
             },
+            Self::Modified(key, modifiers) => {
+                if modifiers.is_empty() {
+                    return key.into_bytes();
+                }
+                let (param, final_byte) = match *key {
+                    Self::Up | Self::KeypadUp => (1, b'A'),
+                    Self::Down | Self::KeypadDown => (1, b'B'),
+                    Self::Right | Self::KeypadRight => (1, b'C'),
+                    Self::Left | Self::KeypadLeft => (1, b'D'),
+                    Self::Home => (1, b'H'),
+                    Self::End => (1, b'F'),
+                    Self::Insert => (2, b'~'),
+                    Self::Delete => (3, b'~'),
+                    Self::PageUp => (5, b'~'),
+                    Self::PageDown => (6, b'~'),
+                    Self::F(1) => (1, b'P'),
+                    Self::F(2) => (1, b'Q'),
+                    Self::F(3) => (1, b'R'),
+                    Self::F(4) => (1, b'S'),
+                    Self::F(c @ 5..=20) => (f_key_param(c), b'~'),
+                    _ => return vec![],
+                };
+                let mut itoa_buf = itoa::Buffer::new();
+                let mut bytes = b"\x1b[".to_vec();
+                bytes.extend(itoa_buf.format(param).as_bytes());
+                bytes.push(b';');
+                bytes.extend(
+                    itoa_buf.format(modifiers.xterm_param()).as_bytes(),
+                );
+                bytes.push(final_byte);
+                bytes
+            }
+            Self::Event(event) => event.into_bytes(),
+            Self::Mouse(event) => event.into_bytes(),
+            Self::FocusGained => b"\x1b[I".to_vec(),
+            Self::FocusLost => b"\x1b[O".to_vec(),
+            Self::Paste(text) => {
+                let mut bytes = crate::PASTE_START.to_vec();
+                bytes.extend(text.into_bytes());
+                bytes.extend(crate::PASTE_END);
+                bytes
+            }
         }
     }
+
+    // the key reported by the given key code in the kitty keyboard
+    // protocol. functional keys which have no equivalent here (such as the
+    // media keys and the modifier keys themselves) return None.
+    pub(crate) fn from_kitty_code(code: u32) -> Option<Self> {
+        let key = match code {
+            8 => Self::Ctrl(b'h'),
+            9 => Self::Ctrl(b'i'),
+            13 | 57414 => Self::Ctrl(b'm'),
+            27 => Self::Escape,
+            127 => Self::Backspace,
+            // f13 through f35
+            57376..=57398 => Self::F(u8::try_from(code - 57376 + 13).ok()?),
+            // the keypad keys
+            57399..=57408 => Self::Char(char::from_digit(code - 57399, 10)?),
+            57409 => Self::Char('.'),
+            57410 => Self::Char('/'),
+            57411 => Self::Char('*'),
+            57412 => Self::Char('-'),
+            57413 => Self::Char('+'),
+            57415 => Self::Char('='),
+            57416 => Self::Char(','),
+            57417 => Self::KeypadLeft,
+            57418 => Self::KeypadRight,
+            57419 => Self::KeypadUp,
+            57420 => Self::KeypadDown,
+            57421 => Self::PageUp,
+            57422 => Self::PageDown,
+            57423 => Self::Home,
+            57424 => Self::End,
+            57425 => Self::Insert,
+            57426 => Self::Delete,
+            // the rest of the private use area is reserved for functional
+            // keys
+            0xe000..=0xf8ff => return None,
+            _ => Self::Char(char::from_u32(code)?),
+        };
+        Some(key)
+    }
+
+    // the f key reported by the given parameter of a `CSI ~` sequence
+    pub(crate) fn from_f_key_param(param: u32) -> Option<Self> {
+        let n = match param {
+            11..=14 => param - 10,
+            15 => 5,
+            17..=21 => param - 11,
+            23..=26 => param - 12,
+            28..=29 => param - 13,
+            31..=34 => param - 14,
+            _ => return None,
+        };
+        u8::try_from(n).ok().map(Self::F)
+    }
+}
+
+impl KeyEvent {
+    /// Returns the bytes which the kitty keyboard protocol uses to report
+    /// this event.
+    #[must_use]
+    pub fn into_bytes(self) -> Vec<u8> {
+        let (param, final_byte) = match self.key {
+            Key::Up | Key::KeypadUp => (1, b'A'),
+            Key::Down | Key::KeypadDown => (1, b'B'),
+            Key::Right | Key::KeypadRight => (1, b'C'),
+            Key::Left | Key::KeypadLeft => (1, b'D'),
+            Key::Home => (1, b'H'),
+            Key::End => (1, b'F'),
+            Key::Insert => (2, b'~'),
+            Key::Delete => (3, b'~'),
+            Key::PageUp => (5, b'~'),
+            Key::PageDown => (6, b'~'),
+            Key::F(1) => (1, b'P'),
+            Key::F(2) => (1, b'Q'),
+            Key::F(3) => (13, b'~'),
+            Key::F(4) => (1, b'S'),
+            Key::F(c @ 5..=20) => (u32::from(f_key_param(c)), b'~'),
+            Key::F(c @ 21..=35) => (57376 + u32::from(c - 13), b'u'),
+            Key::Escape => (27, b'u'),
+            Key::Backspace => (127, b'u'),
+            Key::Ctrl(c @ (b'h' | b'i' | b'm')) => {
+                (u32::from(c - b'a' + 1), b'u')
+            }
+            Key::Char(c) => (u32::from(c), b'u'),
+            _ => return vec![],
+        };
+        let mut itoa_buf = itoa::Buffer::new();
+        let mut bytes = b"\x1b[".to_vec();
+        bytes.extend(itoa_buf.format(param).as_bytes());
+        bytes.push(b';');
+        bytes
+            .extend(itoa_buf.format(self.modifiers.xterm_param()).as_bytes());
+        bytes.push(b':');
+        bytes.extend(itoa_buf.format(self.kind.param()).as_bytes());
+        bytes.push(final_byte);
+        bytes
+    }
+
+    pub(crate) fn from_params(
+        key: Key,
+        modifiers: Option<&[u32]>,
+    ) -> Option<Self> {
+        let (modifiers, kind) = match modifiers {
+            None => (Modifiers::NONE, KeyEventKind::Press),
+            Some([modifiers]) => (
+                Modifiers::from_xterm_param(*modifiers)?,
+                KeyEventKind::Press,
+            ),
+            Some([modifiers, kind, ..]) => (
+                Modifiers::from_xterm_param(*modifiers)?,
+                KeyEventKind::from_param(*kind)?,
+            ),
+            Some([]) => return None,
+        };
+        Some(Self {
+            key,
+            modifiers,
+            kind,
+        })
+    }
+}
+
+fn f_key_param(n: u8) -> u8 {
+    match n {
+        5 => 15,
+        6..=10 => n + 11,
+        11..=14 => n + 12,
+        15..=16 => n + 13,
+        17..=20 => n + 14,
+        _ => 0,
+    }
 }
@@ -0,0 +1,511 @@
+// modifier prefixes, in the order they are displayed
+const MODIFIER_PREFIXES: &[(crate::Modifiers, &str)] = &[
+    (crate::Modifiers::CTRL, "C-"),
+    (crate::Modifiers::ALT, "M-"),
+    (crate::Modifiers::SHIFT, "S-"),
+    (crate::Modifiers::SUPER, "s-"),
+    (crate::Modifiers::HYPER, "H-"),
+    (crate::Modifiers::META, "Meta-"),
+    (crate::Modifiers::CAPS_LOCK, "CapsLock-"),
+    (crate::Modifiers::NUM_LOCK, "NumLock-"),
+];
+
+const KEY_NAMES: &[(crate::Key, &str)] = &[
+    (crate::Key::Backspace, "Backspace"),
+    (crate::Key::Escape, "Esc"),
+    (crate::Key::Up, "Up"),
+    (crate::Key::Down, "Down"),
+    (crate::Key::Right, "Right"),
+    (crate::Key::Left, "Left"),
+    (crate::Key::KeypadUp, "KeypadUp"),
+    (crate::Key::KeypadDown, "KeypadDown"),
+    (crate::Key::KeypadRight, "KeypadRight"),
+    (crate::Key::KeypadLeft, "KeypadLeft"),
+    (crate::Key::Home, "Home"),
+    (crate::Key::End, "End"),
+    (crate::Key::Insert, "Insert"),
+    (crate::Key::Delete, "Delete"),
+    (crate::Key::PageUp, "PageUp"),
+    (crate::Key::PageDown, "PageDown"),
+    (crate::Key::BackTab, "BackTab"),
+    (crate::Key::FocusGained, "FocusGained"),
+    (crate::Key::FocusLost, "FocusLost"),
+    (crate::Key::Ctrl(b'i'), "Tab"),
+    (crate::Key::Ctrl(b'm'), "Enter"),
+    (crate::Key::Char(' '), "Space"),
+];
+
+// accepted when parsing, but never displayed
+const KEY_ALIASES: &[(crate::Key, &str)] = &[
+    (crate::Key::Escape, "Escape"),
+    (crate::Key::Ctrl(b'm'), "Return"),
+    (crate::Key::Delete, "Del"),
+    (crate::Key::Insert, "Ins"),
+];
+
+const MOUSE_BUTTON_NAMES: &[(crate::MouseButton, &str)] = &[
+    (crate::MouseButton::Left, "Left"),
+    (crate::MouseButton::Middle, "Middle"),
+    (crate::MouseButton::Right, "Right"),
+    (crate::MouseButton::WheelUp, "WheelUp"),
+    (crate::MouseButton::WheelDown, "WheelDown"),
+    (crate::MouseButton::WheelLeft, "WheelLeft"),
+    (crate::MouseButton::WheelRight, "WheelRight"),
+];
+
+/// Error returned when parsing a [`Key`](crate::Key) from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseKeyError {
+    input: String,
+}
+
+impl ParseKeyError {
+    fn new(input: &str) -> Self {
+        Self {
+            input: input.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid key: {:?}", self.input)
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
+
+/// Formats keys using the usual notation for key bindings.
+///
+/// Control and alt (meta) keys are written with `C-` and `M-` prefixes (so
+/// `Ctrl(b'a')` is `C-a` and `Meta(b'x')` is `M-x`), and special keys are
+/// written by name (`Up`, `PageDown`, `F5`, `Esc`, `Backspace`, `Tab`,
+/// `Enter`, `Space`, and so on). Modified special keys use the prefixes
+/// `C-`, `M-`, `S-` (shift), `s-` (super), and `H-` (hyper), so
+/// `Modified(Box::new(Key::Up), Modifiers::SHIFT)` is `S-Up`. Any other
+/// character is written as itself, except that `\` is written as `\\` and
+/// control characters are written as `\u{..}`. Raw bytes are written as
+/// `\xNN`. Multi-character keys are written as quoted strings: `"text"` for
+/// [`String`](crate::Key::String), `b"text"` for
+/// [`Bytes`](crate::Key::Bytes), and `Paste("text")` for
+/// [`Paste`](crate::Key::Paste). Kitty keyboard events and mouse events are
+/// written in a longer form, like `Event(a, C-S, Release)` and
+/// `Mouse(Press, Left, C, 4, 10)`.
+///
+/// Parsing the result with [`FromStr`](std::str::FromStr) gives back the
+/// same key, so this notation can be used in configuration files. A
+/// sequence of keys can be parsed by splitting on whitespace:
+///
+/// ```
+/// # use textmode::Key;
+/// let keys: Vec<Key> = "C-x C-s"
+///     .split_whitespace()
+///     .map(str::parse)
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(keys, vec![Key::Ctrl(b'x'), Key::Ctrl(b's')]);
+/// assert_eq!(keys[0].to_string(), "C-x");
+/// ```
+impl std::fmt::Display for crate::Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((_, name)) = KEY_NAMES.iter().find(|(key, _)| key == self)
+        {
+            return f.write_str(name);
+        }
+        match self {
+            Self::Char(c) => write_char(f, *c),
+            Self::Byte(c) => write!(f, "\\x{c:02x}"),
+            Self::Ctrl(c) => {
+                f.write_str("C-")?;
+                write_byte(f, *c)
+            }
+            Self::Meta(c) => {
+                f.write_str("M-")?;
+                write_byte(f, *c)
+            }
+            Self::F(n) => write!(f, "F{n}"),
+            Self::Modified(key, modifiers) => {
+                write_modifier_prefixes(f, *modifiers)?;
+                write!(f, "{key}")
+            }
+            Self::String(s) => write_quoted(f, s),
+            Self::Bytes(bytes) => {
+                f.write_str("b\"")?;
+                for &c in bytes {
+                    match c {
+                        b'\\' => f.write_str("\\\\")?,
+                        b'"' => f.write_str("\\\"")?,
+                        0x20..=0x7e => write!(f, "{}", char::from(c))?,
+                        _ => write!(f, "\\x{c:02x}")?,
+                    }
+                }
+                f.write_str("\"")
+            }
+            Self::Paste(s) => {
+                f.write_str("Paste(")?;
+                write_quoted(f, s)?;
+                f.write_str(")")
+            }
+            Self::Event(event) => {
+                write!(f, "Event({}, ", event.key)?;
+                write_modifiers(f, event.modifiers)?;
+                write!(f, ", {:?})", event.kind)
+            }
+            Self::Mouse(event) => {
+                write!(f, "Mouse({:?}, ", event.kind)?;
+                match event.button {
+                    Some(crate::MouseButton::Other(n)) => {
+                        write!(f, "Button{n}")?;
+                    }
+                    Some(button) => {
+                        let name = MOUSE_BUTTON_NAMES
+                            .iter()
+                            .find(|(b, _)| *b == button)
+                            .map_or("", |(_, name)| name);
+                        f.write_str(name)?;
+                    }
+                    None => f.write_str("None")?,
+                }
+                f.write_str(", ")?;
+                write_modifiers(f, event.modifiers)?;
+                write!(f, ", {}, {})", event.row, event.col)
+            }
+            // these are all handled by KEY_NAMES
+            Self::Backspace
+            | Self::Escape
+            | Self::Up
+            | Self::Down
+            | Self::Right
+            | Self::Left
+            | Self::KeypadUp
+            | Self::KeypadDown
+            | Self::KeypadRight
+            | Self::KeypadLeft
+            | Self::Home
+            | Self::End
+            | Self::Insert
+            | Self::Delete
+            | Self::PageUp
+            | Self::PageDown
+            | Self::BackTab
+            | Self::FocusGained
+            | Self::FocusLost => unreachable!(),
+        }
+    }
+}
+
+impl std::str::FromStr for crate::Key {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_key(s).ok_or_else(|| ParseKeyError::new(s))
+    }
+}
+
+fn write_char(f: &mut std::fmt::Formatter<'_>, c: char) -> std::fmt::Result {
+    if c == '\\' {
+        f.write_str("\\\\")
+    } else if c.is_control() {
+        write!(f, "\\u{{{:x}}}", u32::from(c))
+    } else {
+        write!(f, "{c}")
+    }
+}
+
+fn write_byte(f: &mut std::fmt::Formatter<'_>, c: u8) -> std::fmt::Result {
+    match c {
+        b' ' => f.write_str("Space"),
+        0x21..=0x7e => write_char(f, char::from(c)),
+        _ => write!(f, "\\x{c:02x}"),
+    }
+}
+
+fn write_quoted(
+    f: &mut std::fmt::Formatter<'_>,
+    s: &str,
+) -> std::fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        if c == '"' {
+            f.write_str("\\\"")?;
+        } else {
+            write_char(f, c)?;
+        }
+    }
+    f.write_str("\"")
+}
+
+fn write_modifier_prefixes(
+    f: &mut std::fmt::Formatter<'_>,
+    modifiers: crate::Modifiers,
+) -> std::fmt::Result {
+    for &(modifier, prefix) in MODIFIER_PREFIXES {
+        if modifiers.contains(modifier) {
+            f.write_str(prefix)?;
+        }
+    }
+    Ok(())
+}
+
+// the same as the prefixes, but without the trailing dash
+fn write_modifiers(
+    f: &mut std::fmt::Formatter<'_>,
+    modifiers: crate::Modifiers,
+) -> std::fmt::Result {
+    if modifiers.is_empty() {
+        return f.write_str("None");
+    }
+    let mut first = true;
+    for &(modifier, prefix) in MODIFIER_PREFIXES {
+        if modifiers.contains(modifier) {
+            if !first {
+                f.write_str("-")?;
+            }
+            f.write_str(&prefix[..prefix.len() - 1])?;
+            first = false;
+        }
+    }
+    Ok(())
+}
+
+fn parse_key(s: &str) -> Option<crate::Key> {
+    if let Some(inner) = strip_parens(s, "Paste") {
+        return parse_quoted(inner.strip_prefix('"')?).map(crate::Key::Paste);
+    }
+    if let Some(inner) = strip_parens(s, "Event") {
+        return parse_event(inner);
+    }
+    if let Some(inner) = strip_parens(s, "Mouse") {
+        return parse_mouse(inner);
+    }
+    if s.len() > 1 {
+        if let Some(quoted) = s.strip_prefix("b\"") {
+            return parse_quoted_bytes(quoted).map(crate::Key::Bytes);
+        }
+        if let Some(quoted) = s.strip_prefix('"') {
+            return parse_quoted(quoted).map(crate::Key::String);
+        }
+    }
+
+    let (modifiers, base) = parse_modifier_prefixes(s)?;
+    let key = parse_base(base)?;
+    if modifiers.is_empty() {
+        return Some(key);
+    }
+
+    let byte = match key {
+        crate::Key::Char(c) => u8::try_from(c).ok().filter(u8::is_ascii),
+        crate::Key::Byte(c) => Some(c),
+        _ => None,
+    };
+    match (byte, key) {
+        (Some(c), _) if modifiers == crate::Modifiers::CTRL => {
+            Some(crate::Key::Ctrl(c))
+        }
+        (Some(c), _) if modifiers == crate::Modifiers::ALT => {
+            Some(crate::Key::Meta(c))
+        }
+        (
+            None,
+            key @ (crate::Key::Up
+            | crate::Key::Down
+            | crate::Key::Right
+            | crate::Key::Left
+            | crate::Key::KeypadUp
+            | crate::Key::KeypadDown
+            | crate::Key::KeypadRight
+            | crate::Key::KeypadLeft
+            | crate::Key::Home
+            | crate::Key::End
+            | crate::Key::Insert
+            | crate::Key::Delete
+            | crate::Key::PageUp
+            | crate::Key::PageDown
+            | crate::Key::F(_)),
+        ) => Some(crate::Key::Modified(Box::new(key), modifiers)),
+        _ => None,
+    }
+}
+
+fn parse_base(s: &str) -> Option<crate::Key> {
+    if let Some((key, _)) = KEY_NAMES
+        .iter()
+        .chain(KEY_ALIASES)
+        .find(|(_, name)| *name == s)
+    {
+        return Some(key.clone());
+    }
+    if let Some(n) = s.strip_prefix('F') {
+        if let Ok(n) = n.parse() {
+            return Some(crate::Key::F(n));
+        }
+    }
+    if let Some(hex) = s.strip_prefix("\\x") {
+        if hex.len() == 2 {
+            return u8::from_str_radix(hex, 16).ok().map(crate::Key::Byte);
+        }
+    }
+
+    let mut chars = s.chars();
+    let c = match chars.next()? {
+        '\\' if s.len() > 1 => parse_escape(&mut chars)?,
+        c => c,
+    };
+    chars.next().is_none().then_some(crate::Key::Char(c))
+}
+
+fn parse_modifier_prefixes(mut s: &str) -> Option<(crate::Modifiers, &str)> {
+    let mut modifiers = crate::Modifiers::NONE;
+    'outer: loop {
+        for &(modifier, prefix) in MODIFIER_PREFIXES {
+            if s.len() > prefix.len() && s.starts_with(prefix) {
+                if modifiers.contains(modifier) {
+                    return None;
+                }
+                modifiers |= modifier;
+                s = &s[prefix.len()..];
+                continue 'outer;
+            }
+        }
+        return Some((modifiers, s));
+    }
+}
+
+// the inverse of write_modifiers
+fn parse_modifiers(s: &str) -> Option<crate::Modifiers> {
+    if s == "None" {
+        return Some(crate::Modifiers::NONE);
+    }
+    s.split('-')
+        .try_fold(crate::Modifiers::NONE, |modifiers, name| {
+            MODIFIER_PREFIXES
+                .iter()
+                .find(|(_, prefix)| prefix.strip_suffix('-') == Some(name))
+                .map(|&(modifier, _)| modifiers | modifier)
+        })
+}
+
+fn parse_event(s: &str) -> Option<crate::Key> {
+    let mut parts = s.rsplitn(3, ", ");
+    let kind = match parts.next()? {
+        "Press" => crate::KeyEventKind::Press,
+        "Repeat" => crate::KeyEventKind::Repeat,
+        "Release" => crate::KeyEventKind::Release,
+        _ => return None,
+    };
+    let modifiers = parse_modifiers(parts.next()?)?;
+    let key = parse_key(parts.next()?)?;
+    Some(crate::Key::Event(Box::new(crate::KeyEvent {
+        key,
+        modifiers,
+        kind,
+    })))
+}
+
+fn parse_mouse(s: &str) -> Option<crate::Key> {
+    let parts: Vec<_> = s.split(", ").collect();
+    let [kind, button, modifiers, row, col] = parts[..] else {
+        return None;
+    };
+    let kind = match kind {
+        "Press" => crate::MouseEventKind::Press,
+        "Release" => crate::MouseEventKind::Release,
+        "Drag" => crate::MouseEventKind::Drag,
+        "Move" => crate::MouseEventKind::Move,
+        _ => return None,
+    };
+    let button = if button == "None" {
+        None
+    } else if let Some(n) = button.strip_prefix("Button") {
+        Some(crate::MouseButton::Other(n.parse().ok()?))
+    } else {
+        Some(
+            MOUSE_BUTTON_NAMES
+                .iter()
+                .find(|(_, name)| *name == button)?
+                .0,
+        )
+    };
+    Some(crate::Key::Mouse(crate::MouseEvent {
+        kind,
+        button,
+        modifiers: parse_modifiers(modifiers)?,
+        row: row.parse().ok()?,
+        col: col.parse().ok()?,
+    }))
+}
+
+// parses the rest of a quoted string, after the opening quote
+fn parse_quoted(s: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    loop {
+        match chars.next()? {
+            '"' => return chars.next().is_none().then_some(out),
+            '\\' => out.push(parse_escape(&mut chars)?),
+            c => out.push(c),
+        }
+    }
+}
+
+fn parse_quoted_bytes(s: &str) -> Option<Vec<u8>> {
+    let mut out = vec![];
+    let mut chars = s.chars();
+    loop {
+        match chars.next()? {
+            '"' => return chars.next().is_none().then_some(out),
+            '\\' => {
+                let rest = chars.as_str();
+                if let Some(hex) = rest.strip_prefix('x') {
+                    out.push(u8::from_str_radix(hex.get(..2)?, 16).ok()?);
+                    chars = hex[2..].chars();
+                } else {
+                    let c = parse_escape(&mut chars)?;
+                    out.extend(c.to_string().bytes());
+                }
+            }
+            c => out.extend(c.to_string().bytes()),
+        }
+    }
+}
+
+// parses the rest of an escape sequence, after the backslash
+fn parse_escape(chars: &mut std::str::Chars<'_>) -> Option<char> {
+    match chars.next()? {
+        c @ ('\\' | '"') => Some(c),
+        'u' => {
+            let rest = chars.as_str().strip_prefix('{')?;
+            let end = rest.find('}')?;
+            let c =
+                char::from_u32(u32::from_str_radix(&rest[..end], 16).ok()?)?;
+            *chars = rest[end + 1..].chars();
+            Some(c)
+        }
+        _ => None,
+    }
+}
+
+fn strip_parens<'a>(s: &'a str, name: &str) -> Option<&'a str> {
+    s.strip_prefix(name)?.strip_prefix('(')?.strip_suffix(')')
+}
+
+/// Keys are serialized as strings, using the same notation as `Display`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for crate::Key {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for crate::Key {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
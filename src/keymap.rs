@@ -0,0 +1,249 @@
+/// A set of key bindings, which map sequences of keys to actions.
+///
+/// Sequences can be made up of multiple keys (like `C-x C-s` in emacs or the
+/// `C-b` prefix in tmux). Keys are matched incrementally as they are fed in
+/// with [`feed`](Keymap::feed), and keys which don't form part of any bound
+/// sequence are passed back to the caller so that they can be handled
+/// normally. The simplest way to use a keymap is with
+/// [`Input::read_binding`](crate::blocking::Input::read_binding), which also
+/// handles the timeout.
+///
+/// ```
+/// # use textmode::{Key, Keymap, KeymapEvent};
+/// let mut keymap = Keymap::new();
+/// keymap.bind([Key::Ctrl(b'x'), Key::Ctrl(b's')], "save");
+///
+/// assert_eq!(keymap.feed(Key::Ctrl(b'x')), KeymapEvent::Pending);
+/// assert_eq!(keymap.feed(Key::Ctrl(b's')), KeymapEvent::Action("save"));
+/// assert_eq!(
+///     keymap.feed(Key::Char('a')),
+///     KeymapEvent::Unmatched(vec![Key::Char('a')]),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Keymap<T> {
+    root: Node<T>,
+    timeout: Option<std::time::Duration>,
+    pending: Vec<crate::Key>,
+    pending_since: Option<std::time::Instant>,
+    ready: std::collections::VecDeque<KeymapEvent<T>>,
+}
+
+/// The result of feeding a key into a [`Keymap`].
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum KeymapEvent<T> {
+    /// A bound sequence was completed, and this is its action.
+    Action(T),
+    /// The keys so far are a prefix of at least one bound sequence, and more
+    /// keys are needed to tell which one.
+    Pending,
+    /// The keys don't match any bound sequence. This contains every key
+    /// which was part of the failed match (including any previously pending
+    /// keys), in order, so that they can be handled as normal input.
+    Unmatched(Vec<crate::Key>),
+}
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    action: Option<T>,
+    children: Vec<(crate::Key, Self)>,
+}
+
+impl<T> Node<T> {
+    fn child(&self, key: &crate::Key) -> Option<&Self> {
+        self.children
+            .iter()
+            .find(|(child_key, _)| child_key == key)
+            .map(|(_, node)| node)
+    }
+}
+
+impl<T> Default for Node<T> {
+    fn default() -> Self {
+        Self {
+            action: None,
+            children: vec![],
+        }
+    }
+}
+
+impl<T: Clone> Keymap<T> {
+    /// Creates a new empty `Keymap`, with no timeout.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            root: Node::default(),
+            timeout: None,
+            pending: vec![],
+            pending_since: None,
+            ready: std::collections::VecDeque::new(),
+        }
+    }
+
+    /// Binds a sequence of keys to an action, replacing any action
+    /// previously bound to the same sequence. Binding an empty sequence does
+    /// nothing.
+    ///
+    /// A sequence may also be a prefix of another bound sequence. In that
+    /// case, the shorter sequence's action is only triggered once the
+    /// timeout expires (or [`flush`](Keymap::flush) is called), or once a
+    /// key is received which doesn't continue the longer sequence. Any keys
+    /// after the shorter sequence are then matched again from the start.
+    pub fn bind(
+        &mut self,
+        keys: impl IntoIterator<Item = crate::Key>,
+        action: T,
+    ) {
+        let mut node = &mut self.root;
+        let mut empty = true;
+        for key in keys {
+            empty = false;
+            let idx = node
+                .children
+                .iter()
+                .position(|(child_key, _)| *child_key == key)
+                .unwrap_or_else(|| {
+                    node.children.push((key, Node::default()));
+                    node.children.len() - 1
+                });
+            node = &mut node.children[idx].1;
+        }
+        if !empty {
+            node.action = Some(action);
+        }
+    }
+
+    /// Sets how long to wait for the next key of a partially entered
+    /// sequence. When the timeout expires, the pending keys are reported as
+    /// [`Unmatched`](KeymapEvent::Unmatched) (or as the
+    /// [`Action`](KeymapEvent::Action) bound to them, as described by
+    /// [`flush`](Keymap::flush)).
+    /// Defaults to `None`, which waits forever.
+    pub fn timeout(&mut self, timeout: Option<std::time::Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Feeds a key into the keymap, and returns what it matched.
+    ///
+    /// A single key can complete more than one event, when it ends a bound
+    /// sequence which is also a prefix of a longer one (the shorter
+    /// sequence's action is returned, and the keys after it are matched
+    /// again). Only the first event is returned here, and the rest are
+    /// returned by [`next_event`](Keymap::next_event).
+    pub fn feed(&mut self, key: crate::Key) -> KeymapEvent<T> {
+        self.process(key);
+        self.ready.pop_front().unwrap_or(KeymapEvent::Pending)
+    }
+
+    /// Ends the current partially entered sequence, if there is one. This
+    /// returns the [`Action`](KeymapEvent::Action) bound to the longest
+    /// prefix of the pending keys if there is one (matching the keys after
+    /// it again), and otherwise returns them as
+    /// [`Unmatched`](KeymapEvent::Unmatched). As with
+    /// [`feed`](Keymap::feed), any further events are returned by
+    /// [`next_event`](Keymap::next_event). Returns `None` if no keys are
+    /// pending.
+    pub fn flush(&mut self) -> Option<KeymapEvent<T>> {
+        while !self.pending.is_empty() {
+            self.resolve();
+        }
+        self.ready.pop_front()
+    }
+
+    /// Returns the next event completed by an earlier call to
+    /// [`feed`](Keymap::feed) or [`flush`](Keymap::flush) which wasn't
+    /// returned by it, if there is one. This should be called until it
+    /// returns `None` after each call to either of them.
+    /// [`Input::read_binding`](crate::blocking::Input::read_binding) does
+    /// this automatically.
+    pub fn next_event(&mut self) -> Option<KeymapEvent<T>> {
+        self.ready.pop_front()
+    }
+
+    /// Returns the keys of the current partially entered sequence.
+    #[must_use]
+    pub fn pending(&self) -> &[crate::Key] {
+        &self.pending
+    }
+
+    /// Returns true if a partially entered sequence is waiting for more
+    /// keys.
+    #[must_use]
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Returns the time at which the current partially entered sequence
+    /// will time out, if there is one and a timeout is set. Once this time
+    /// has passed, [`flush`](Keymap::flush) should be called.
+    #[must_use]
+    pub fn deadline(&self) -> Option<std::time::Instant> {
+        Some(self.pending_since? + self.timeout?)
+    }
+
+    fn process(&mut self, key: crate::Key) {
+        let Some(node) =
+            self.pending_node().and_then(|node| node.child(&key))
+        else {
+            self.pending.push(key);
+            self.resolve();
+            return;
+        };
+
+        if !node.children.is_empty() {
+            self.pending.push(key);
+            self.pending_since = Some(std::time::Instant::now());
+            return;
+        }
+
+        let action = node.action.clone();
+        self.pending.push(key);
+        self.pending_since = None;
+        let pending = std::mem::take(&mut self.pending);
+        self.ready.push_back(action.map_or_else(
+            || KeymapEvent::Unmatched(pending),
+            KeymapEvent::Action,
+        ));
+    }
+
+    // ends the pending sequence. if some prefix of it is bound, the longest
+    // one is reported as its action and the keys after it are fed in again
+    // (like vim and tmux do), and otherwise all of the keys are unmatched.
+    fn resolve(&mut self) {
+        let mut pending = std::mem::take(&mut self.pending);
+        self.pending_since = None;
+
+        let mut node = &self.root;
+        let mut longest = None;
+        for (i, key) in pending.iter().enumerate() {
+            let Some(child) = node.child(key) else {
+                break;
+            };
+            node = child;
+            if let Some(action) = &node.action {
+                longest = Some((i + 1, action.clone()));
+            }
+        }
+
+        let Some((len, action)) = longest else {
+            self.ready.push_back(KeymapEvent::Unmatched(pending));
+            return;
+        };
+        self.ready.push_back(KeymapEvent::Action(action));
+        for key in pending.split_off(len) {
+            self.process(key);
+        }
+    }
+
+    fn pending_node(&self) -> Option<&Node<T>> {
+        self.pending
+            .iter()
+            .try_fold(&self.root, |node, key| node.child(key))
+    }
+}
+
+impl<T: Clone> Default for Keymap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
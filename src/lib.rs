@@ -75,11 +75,36 @@ pub mod blocking;
 
 pub mod color;
 pub use vt100::Color;
+mod color_depth;
+pub use color_depth::ColorDepth;
+mod draw;
+pub use draw::BorderStyle;
 mod error;
 pub use error::{Error, Result};
+mod ext;
+mod inline;
+pub use inline::InlineCleanup;
 mod key;
-pub use key::Key;
+pub use key::{Key, KeyEvent, KeyEventKind, KittyFlags, Modifiers};
+mod key_notation;
+pub use key_notation::ParseKeyError;
+mod keymap;
+pub use keymap::{Keymap, KeymapEvent};
+mod mouse;
+pub use mouse::{MouseButton, MouseEvent, MouseEventKind, MouseMode};
 mod private;
+mod resize;
+mod snapshot;
+pub use snapshot::{Snapshot, SnapshotCell};
+mod style;
+pub use style::{Span, Style, UnderlineStyle};
+mod terminfo;
+pub mod text;
+#[cfg(feature = "async")]
+pub use resize::ResizeWatcher;
+pub use terminfo::TerminfoKeys;
+mod view;
+pub use view::View;
 
 #[cfg(feature = "async")]
 mod output;
@@ -88,10 +113,29 @@ pub use output::{Output, ScreenGuard};
 #[cfg(feature = "async")]
 mod input;
 #[cfg(feature = "async")]
-pub use input::{Input, RawGuard};
+pub use input::{Input, InputStream, RawGuard};
 
 const INIT: &[u8] = b"\x1b7\x1b[?47h\x1b[2J\x1b[H\x1b[?25h";
 const DEINIT: &[u8] = b"\x1b[?47l\x1b8\x1b[?25h";
+const KITTY_KEYBOARD_POP: &[u8] = b"\x1b[<u";
+const BRACKETED_PASTE_ENABLE: &[u8] = b"\x1b[?2004h";
+const BRACKETED_PASTE_DISABLE: &[u8] = b"\x1b[?2004l";
+const FOCUS_REPORTING_ENABLE: &[u8] = b"\x1b[?1004h";
+const FOCUS_REPORTING_DISABLE: &[u8] = b"\x1b[?1004l";
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+fn terminal_size() -> (u16, u16) {
+    match terminal_size::terminal_size() {
+        Some((terminal_size::Width(w), terminal_size::Height(h))) => (h, w),
+        _ => (24, 80),
+    }
+}
+
+fn terminal_size_of(fd: impl std::os::fd::AsFd) -> Option<(u16, u16)> {
+    terminal_size::terminal_size_of(fd)
+        .map(|(terminal_size::Width(w), terminal_size::Height(h))| (h, w))
+}
 
 /// Provides the methods used to manipulate the in-memory screen.
 pub trait Textmode: private::Output {
@@ -104,13 +148,14 @@ pub trait Textmode: private::Output {
     /// Writes a sequence of bytes, potentially containing terminal escape
     /// sequences, to the in-memory screen.
     fn write(&mut self, buf: &[u8]) {
-        self.next_mut().process(buf);
+        self.process(buf);
     }
 
     /// Sets the terminal size for the in-memory screen.
     fn set_size(&mut self, rows: u16, cols: u16) {
         self.cur_mut().set_size(rows, cols);
         self.next_mut().set_size(rows, cols);
+        self.ext_mut().set_size(rows, cols);
     }
 
     /// Writes a string of printable characters to the in-memory screen.
@@ -237,7 +282,27 @@ pub trait Textmode: private::Output {
         if bold {
             self.write(b"\x1b[1m");
         } else {
+            // this also turns off dim
+            let dim = self.ext().attrs().dim();
+            self.write(b"\x1b[22m");
+            if dim {
+                self.write(b"\x1b[2m");
+            }
+        }
+    }
+
+    /// Sets whether subsequent text drawn to the in-memory screen should be
+    /// dimmer than usual.
+    fn set_dim(&mut self, dim: bool) {
+        if dim {
+            self.write(b"\x1b[2m");
+        } else {
+            // this also turns off bold
+            let bold = self.screen().bold();
             self.write(b"\x1b[22m");
+            if bold {
+                self.write(b"\x1b[1m");
+            }
         }
     }
 
@@ -271,6 +336,93 @@ pub trait Textmode: private::Output {
         }
     }
 
+    /// Sets whether subsequent text drawn to the in-memory screen should be
+    /// underlined with the given kind of underline. Use
+    /// [`set_underline`](Textmode::set_underline) to turn it off again.
+    fn set_underline_style(&mut self, style: UnderlineStyle) {
+        self.write(b"\x1b[4:");
+        self.write_u8(style.param());
+        self.write(b"m");
+    }
+
+    /// Sets the color of the underline for subsequent text drawn to the
+    /// in-memory screen.
+    fn set_underline_color(&mut self, color: vt100::Color) {
+        match color {
+            vt100::Color::Default => {
+                self.write(b"\x1b[59m");
+            }
+            vt100::Color::Idx(i) => {
+                self.write(b"\x1b[58:5:");
+                self.write_u8(i);
+                self.write(b"m");
+            }
+            vt100::Color::Rgb(r, g, b) => {
+                self.write(b"\x1b[58:2::");
+                self.write_u8(r);
+                self.write(b":");
+                self.write_u8(g);
+                self.write(b":");
+                self.write_u8(b);
+                self.write(b"m");
+            }
+        }
+    }
+
+    /// Sets whether subsequent text drawn to the in-memory screen should
+    /// blink.
+    fn set_blink(&mut self, blink: bool) {
+        if blink {
+            self.write(b"\x1b[5m");
+        } else {
+            self.write(b"\x1b[25m");
+        }
+    }
+
+    /// Sets whether subsequent text drawn to the in-memory screen should be
+    /// hidden.
+    fn set_hidden(&mut self, hidden: bool) {
+        if hidden {
+            self.write(b"\x1b[8m");
+        } else {
+            self.write(b"\x1b[28m");
+        }
+    }
+
+    /// Sets whether subsequent text drawn to the in-memory screen should be
+    /// struck through.
+    fn set_strikethrough(&mut self, strikethrough: bool) {
+        if strikethrough {
+            self.write(b"\x1b[9m");
+        } else {
+            self.write(b"\x1b[29m");
+        }
+    }
+
+    /// Sets whether subsequent text drawn to the in-memory screen should
+    /// have a line drawn over it.
+    fn set_overline(&mut self, overline: bool) {
+        if overline {
+            self.write(b"\x1b[53m");
+        } else {
+            self.write(b"\x1b[55m");
+        }
+    }
+
+    /// Returns the drawing attributes which are currently active on the
+    /// in-memory screen.
+    fn current_style(&self) -> Style {
+        Style::from_screen(self.screen(), self.ext().attrs())
+    }
+
+    /// Sets all of the drawing attributes for subsequent drawing operations
+    /// to the in-memory screen at once. Only the attributes which differ
+    /// from the [`current_style`](Textmode::current_style) are changed.
+    fn set_style(&mut self, style: Style) {
+        let cur = self.current_style();
+        style.apply(self, cur);
+    }
+
     /// Sets whether the cursor should be visible.
     fn hide_cursor(&mut self, hide: bool) {
         if hide {
@@ -279,4 +431,168 @@ pub trait Textmode: private::Output {
             self.write(b"\x1b[?25h");
         }
     }
+
+    /// Returns a copy of the contents of the in-memory screen, including
+    /// the cursor position and the active drawing attributes.
+    fn snapshot(&self) -> Snapshot {
+        Snapshot::new(self.screen(), self.ext())
+    }
+
+    /// Replaces the contents of the in-memory screen with the contents of a
+    /// snapshot returned by [`snapshot`](Textmode::snapshot), resizing it to
+    /// match if necessary. This can be used to restore a screen into a
+    /// fresh `Output`, which will then draw it on the next call to
+    /// `refresh`.
+    fn restore_snapshot(&mut self, snapshot: &Snapshot) {
+        snapshot.restore(self);
+    }
+
+    /// Returns a [`View`] of the rectangular region of the in-memory screen
+    /// with its top left corner at `(row, col)`, `rows` lines tall and
+    /// `cols` columns wide. Drawing through the view is relative to that
+    /// corner, and is clipped at the edges of the region.
+    fn view(
+        &mut self,
+        row: u16,
+        col: u16,
+        rows: u16,
+        cols: u16,
+    ) -> View<'_, Self>
+    where
+        Self: Sized,
+    {
+        View::new(self, row, col, rows, cols)
+    }
+}
+
+/// Drawing operations shared by outputs and [`View`]s.
+///
+/// Code which draws something can be written once against this trait and
+/// used with either. When drawing to a view, positions are relative to the
+/// view, and anything outside of it is clipped.
+pub trait Draw: private::Draw {
+    /// Writes `text`, shortened to at most `width` columns with
+    /// [`text::truncate`] (using [`text::ELLIPSIS`]) if it doesn't fit.
+    fn write_truncated(&mut self, text: &str, width: usize) {
+        self.put_str(&crate::text::truncate(
+            text,
+            width,
+            crate::text::ELLIPSIS,
+        ));
+    }
+
+    /// Writes `text`, padded to exactly `width` columns as described by
+    /// [`text::align`].
+    fn write_aligned(
+        &mut self,
+        text: &str,
+        width: usize,
+        align: crate::text::Align,
+    ) {
+        self.put_str(&crate::text::align(text, width, align));
+    }
+
+    /// Writes `text`, word wrapped to `width` columns with [`text::wrap`].
+    /// Each line after the first starts on the next row, in the column that
+    /// the cursor started in. Returns the number of lines written.
+    fn write_wrapped(&mut self, text: &str, width: usize) -> usize {
+        let (row, col) = self.cursor();
+        let lines = crate::text::wrap(text, width);
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                let Ok(i) = u16::try_from(i) else {
+                    break;
+                };
+                self.goto(row.saturating_add(i), col);
+            }
+            self.put_str(line);
+        }
+        lines.len()
+    }
+
+    /// Writes a string of printable characters using `style`, and then
+    /// switches back to the style that was active before.
+    fn write_styled(&mut self, text: &str, style: Style) {
+        self.write_spans(&[Span::new(text, style)]);
+    }
+
+    /// Writes each span in order, using its style, and then switches back
+    /// to the style that was active before.
+    fn write_spans(&mut self, spans: &[Span<'_>]) {
+        let prev = self.style();
+        for span in spans {
+            self.put_style(span.style);
+            self.put_str(&span.text);
+        }
+        self.put_style(prev);
+    }
+
+    /// Fills a rectangular region with the character `c` (which should be a
+    /// single column wide), using the currently active drawing attributes.
+    /// The cursor is not moved.
+    fn fill_rect(
+        &mut self,
+        row: u16,
+        col: u16,
+        rows: u16,
+        cols: u16,
+        c: char,
+    ) {
+        let (tm, clip) = self.target();
+        let (row, col) = clip.offset(row, col);
+        draw::fill_rect(tm, row, col, rows, cols, c, clip);
+    }
+
+    /// Draws a horizontal line `len` columns long, starting at `(row, col)`
+    /// and extending to the right, using the currently active drawing
+    /// attributes. Where it meets or crosses lines that were already drawn,
+    /// the appropriate junction character is used. The cursor is not
+    /// moved.
+    fn draw_hline(
+        &mut self,
+        row: u16,
+        col: u16,
+        len: u16,
+        style: BorderStyle,
+    ) {
+        let (tm, clip) = self.target();
+        let (row, col) = clip.offset(row, col);
+        draw::hline(tm, row, col, len, style, clip);
+    }
+
+    /// Draws a vertical line `len` rows long, starting at `(row, col)` and
+    /// extending downward, using the currently active drawing attributes.
+    /// Where it meets or crosses lines that were already drawn, the
+    /// appropriate junction character is used. The cursor is not moved.
+    fn draw_vline(
+        &mut self,
+        row: u16,
+        col: u16,
+        len: u16,
+        style: BorderStyle,
+    ) {
+        let (tm, clip) = self.target();
+        let (row, col) = clip.offset(row, col);
+        draw::vline(tm, row, col, len, style, clip);
+    }
+
+    /// Draws the border of a box with its top left corner at `(row, col)`,
+    /// `rows` lines tall and `cols` columns wide, using the currently active
+    /// drawing attributes. The inside of the box is left alone. Where the
+    /// border meets or crosses lines that were already drawn, the
+    /// appropriate junction character is used. The cursor is not moved.
+    fn draw_box(
+        &mut self,
+        row: u16,
+        col: u16,
+        rows: u16,
+        cols: u16,
+        style: BorderStyle,
+    ) {
+        let (tm, clip) = self.target();
+        let (row, col) = clip.offset(row, col);
+        draw::draw_box(tm, row, col, rows, cols, style, clip);
+    }
 }
+
+impl<T: Textmode> Draw for T {}
@@ -0,0 +1,192 @@
+/// Which mouse events the terminal should report.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum MouseMode {
+    /// Report button presses and releases (including the scroll wheel).
+    Click,
+    /// Additionally report mouse movement while a button is held down.
+    Drag,
+    /// Additionally report all mouse movement.
+    Motion,
+}
+
+impl MouseMode {
+    fn param(self) -> &'static [u8] {
+        match self {
+            Self::Click => b"1000",
+            Self::Drag => b"1002",
+            Self::Motion => b"1003",
+        }
+    }
+
+    pub(crate) fn enable_sequence(self) -> Vec<u8> {
+        let mut bytes = b"\x1b[?".to_vec();
+        bytes.extend(self.param());
+        bytes.extend(b"h\x1b[?1006h");
+        bytes
+    }
+
+    pub(crate) fn disable_sequence(self) -> Vec<u8> {
+        let mut bytes = b"\x1b[?1006l\x1b[?".to_vec();
+        bytes.extend(self.param());
+        bytes.push(b'l');
+        bytes
+    }
+}
+
+/// A mouse button.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum MouseButton {
+    /// The left button.
+    Left,
+    /// The middle button (or pressing the scroll wheel).
+    Middle,
+    /// The right button.
+    Right,
+    /// Scrolling the wheel up.
+    WheelUp,
+    /// Scrolling the wheel down.
+    WheelDown,
+    /// Scrolling the wheel left (or tilting it, on some mice).
+    WheelLeft,
+    /// Scrolling the wheel right (or tilting it, on some mice).
+    WheelRight,
+    /// Additional buttons, such as back and forward buttons. The associated
+    /// value is the button number, starting at 8.
+    Other(u8),
+}
+
+/// The type of a [`MouseEvent`].
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum MouseEventKind {
+    /// A button was pressed. Scrolling the mouse wheel is reported as
+    /// pressing one of the `Wheel*` buttons.
+    Press,
+    /// A button was released. Note that terminals don't report releases
+    /// for the mouse wheel.
+    Release,
+    /// The mouse was moved while a button was held down. Only reported when
+    /// mouse reporting is enabled with [`MouseMode::Drag`] or
+    /// [`MouseMode::Motion`].
+    Drag,
+    /// The mouse was moved with no buttons held down. Only reported when
+    /// mouse reporting is enabled with [`MouseMode::Motion`].
+    Move,
+}
+
+/// A mouse event reported by the terminal. These are only produced after
+/// enabling mouse reporting with `enable_mouse` on `Input` or `RawGuard`.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub struct MouseEvent {
+    /// What happened.
+    pub kind: MouseEventKind,
+    /// The button involved in the event. This will be `None` for
+    /// [`Move`](MouseEventKind::Move) events.
+    pub button: Option<MouseButton>,
+    /// The modifier keys held down during the event. Only
+    /// [`SHIFT`](crate::Modifiers::SHIFT), [`ALT`](crate::Modifiers::ALT),
+    /// and [`CTRL`](crate::Modifiers::CTRL) are reported.
+    pub modifiers: crate::Modifiers,
+    /// The row the mouse was on, starting at 0.
+    pub row: u16,
+    /// The column the mouse was on, starting at 0.
+    pub col: u16,
+}
+
+impl MouseEvent {
+    /// Returns the bytes which SGR mouse reporting uses to report this
+    /// event.
+    #[must_use]
+    pub fn into_bytes(self) -> Vec<u8> {
+        let mut code: u32 = match self.button {
+            Some(MouseButton::Left) => 0,
+            Some(MouseButton::Middle) => 1,
+            Some(MouseButton::Right) => 2,
+            None => 3,
+            Some(MouseButton::WheelUp) => 64,
+            Some(MouseButton::WheelDown) => 65,
+            Some(MouseButton::WheelLeft) => 66,
+            Some(MouseButton::WheelRight) => 67,
+            Some(MouseButton::Other(n)) => {
+                128 + (u32::from(n.saturating_sub(8)) & 0b11)
+            }
+        };
+        if self.modifiers.shift() {
+            code += 4;
+        }
+        if self.modifiers.alt() {
+            code += 8;
+        }
+        if self.modifiers.ctrl() {
+            code += 16;
+        }
+        if matches!(self.kind, MouseEventKind::Drag | MouseEventKind::Move) {
+            code += 32;
+        }
+
+        let mut itoa_buf = itoa::Buffer::new();
+        let mut bytes = b"\x1b[<".to_vec();
+        bytes.extend(itoa_buf.format(code).as_bytes());
+        bytes.push(b';');
+        bytes.extend(itoa_buf.format(u32::from(self.col) + 1).as_bytes());
+        bytes.push(b';');
+        bytes.extend(itoa_buf.format(u32::from(self.row) + 1).as_bytes());
+        bytes.push(if self.kind == MouseEventKind::Release {
+            b'm'
+        } else {
+            b'M'
+        });
+        bytes
+    }
+
+    pub(crate) fn from_sgr_params(
+        code: u32,
+        col: u32,
+        row: u32,
+        release: bool,
+    ) -> Option<Self> {
+        let button = match (code & 0b1100_0011, code & 0b0010_0000 != 0) {
+            (0, _) => Some(MouseButton::Left),
+            (1, _) => Some(MouseButton::Middle),
+            (2, _) => Some(MouseButton::Right),
+            (3, _) => None,
+            (64, false) => Some(MouseButton::WheelUp),
+            (65, false) => Some(MouseButton::WheelDown),
+            (66, false) => Some(MouseButton::WheelLeft),
+            (67, false) => Some(MouseButton::WheelRight),
+            (n @ 128..=131, _) => {
+                Some(MouseButton::Other(u8::try_from(n - 120).ok()?))
+            }
+            _ => return None,
+        };
+        let kind = if release {
+            MouseEventKind::Release
+        } else if code & 0b0010_0000 != 0 {
+            if button.is_some() {
+                MouseEventKind::Drag
+            } else {
+                MouseEventKind::Move
+            }
+        } else {
+            MouseEventKind::Press
+        };
+
+        let mut modifiers = crate::Modifiers::NONE;
+        if code & 0b0000_0100 != 0 {
+            modifiers |= crate::Modifiers::SHIFT;
+        }
+        if code & 0b0000_1000 != 0 {
+            modifiers |= crate::Modifiers::ALT;
+        }
+        if code & 0b0001_0000 != 0 {
+            modifiers |= crate::Modifiers::CTRL;
+        }
+
+        Some(Self {
+            kind,
+            button,
+            modifiers,
+            row: u16::try_from(row.checked_sub(1)?).ok()?,
+            col: u16::try_from(col.checked_sub(1)?).ok()?,
+        })
+    }
+}
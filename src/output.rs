@@ -1,11 +1,15 @@
 use tokio::io::AsyncWriteExt as _;
 
 use crate::private::Output as _;
+use crate::Textmode as _;
 
-/// Switches the terminal on `stdout` to alternate screen mode, and restores
-/// it when this object goes out of scope.
+/// Switches the terminal on `stdout` (or another terminal) to alternate
+/// screen mode, and restores it when this object goes out of scope.
 pub struct ScreenGuard {
+    // None means stdout
+    tty: Option<std::sync::Arc<std::fs::File>>,
     cleaned_up: bool,
+    bracketed_paste: bool,
 }
 
 impl ScreenGuard {
@@ -16,8 +20,55 @@ impl ScreenGuard {
     /// # Errors
     /// * `Error::WriteStdout`: failed to write initialization to stdout
     pub async fn new() -> crate::error::Result<Self> {
-        write_stdout(&mut tokio::io::stdout(), crate::INIT).await?;
-        Ok(Self { cleaned_up: false })
+        Self::new_for_tty(None).await
+    }
+
+    /// Switches the given terminal to alternate screen mode and returns a
+    /// guard object. This is typically called as part of
+    /// [`Output::from_fd`](Output::from_fd).
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to duplicate the file descriptor
+    /// * `Error::WriteStdout`: failed to write initialization to the
+    ///   terminal
+    pub async fn from_fd(
+        fd: impl std::os::fd::AsFd,
+    ) -> crate::error::Result<Self> {
+        let tty = fd
+            .as_fd()
+            .try_clone_to_owned()
+            .map_err(crate::error::Error::OpenTerminal)?;
+        Self::new_for_tty(Some(std::sync::Arc::new(tty.into()))).await
+    }
+
+    async fn new_for_tty(
+        tty: Option<std::sync::Arc<std::fs::File>>,
+    ) -> crate::error::Result<Self> {
+        write_terminal(tty.as_ref(), crate::INIT).await?;
+        Ok(Self {
+            tty,
+            cleaned_up: false,
+            bracketed_paste: false,
+        })
+    }
+
+    /// Enables bracketed paste mode, by writing the appropriate escape
+    /// sequence to `stdout`. This causes pasted text to be reported as a
+    /// single [`Paste`](crate::Key::Paste) key by `read_key`, rather than as
+    /// individual keypresses. Bracketed paste mode will be disabled again
+    /// when this guard is cleaned up.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to stdout
+    pub async fn enable_bracketed_paste(
+        &mut self,
+    ) -> crate::error::Result<()> {
+        if !self.bracketed_paste {
+            write_terminal(self.tty.as_ref(), crate::BRACKETED_PASTE_ENABLE)
+                .await?;
+            self.bracketed_paste = true;
+        }
+        Ok(())
     }
 
     /// Switch back from alternate screen mode early.
@@ -29,7 +80,16 @@ impl ScreenGuard {
             return Ok(());
         }
         self.cleaned_up = true;
-        write_stdout(&mut tokio::io::stdout(), crate::DEINIT).await
+        write_terminal(self.tty.as_ref(), &self.deinit_sequence()).await
+    }
+
+    fn deinit_sequence(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        if self.bracketed_paste {
+            buf.extend(crate::BRACKETED_PASTE_DISABLE);
+        }
+        buf.extend(crate::DEINIT);
+        buf
     }
 }
 
@@ -48,25 +108,44 @@ impl Drop for ScreenGuard {
         use std::io::Write as _;
 
         if !self.cleaned_up {
-            let mut stdout = std::io::stdout();
-            let _ = stdout.write_all(crate::DEINIT);
-            let _ = stdout.flush();
+            if let Some(tty) = &self.tty {
+                let _ = crate::blocking::output::write_tty(
+                    tty,
+                    &self.deinit_sequence(),
+                );
+            } else {
+                let mut stdout = std::io::stdout();
+                let _ = stdout.write_all(&self.deinit_sequence());
+                let _ = stdout.flush();
+            }
         }
     }
 }
 
-/// Manages drawing to the terminal on `stdout`.
+/// Manages drawing to the terminal on `stdout` (or another terminal or
+/// writer).
 ///
 /// Most functionality is provided by the [`Textmode`](crate::Textmode) trait.
 /// You should call those trait methods to draw to the in-memory screen, and
 /// then call [`refresh`](Output::refresh) when you want to update the
 /// terminal on `stdout`.
+///
+/// By default, the in-memory screen covers the whole terminal. Alternatively,
+/// [`new_inline`](Output::new_inline) creates an `Output` which only draws
+/// to a fixed number of lines starting at the current cursor position,
+/// without switching to the alternate screen, which is useful for things
+/// like prompts and progress displays.
 pub struct Output {
-    stdout: tokio::io::Stdout,
+    sink: Sink,
     screen: Option<ScreenGuard>,
+    resize: Option<crate::resize::ResizeFlag>,
+    color_depth: crate::ColorDepth,
+    inline: Option<crate::InlineCleanup>,
 
     cur: vt100::Parser,
     next: vt100::Parser,
+    ext: crate::ext::Ext,
+    lines: crate::draw::Lines,
 }
 
 impl crate::private::Output for Output {
@@ -85,6 +164,18 @@ impl crate::private::Output for Output {
     fn next_mut(&mut self) -> &mut vt100::Parser {
         &mut self.next
     }
+
+    fn ext(&self) -> &crate::ext::Ext {
+        &self.ext
+    }
+
+    fn ext_mut(&mut self) -> &mut crate::ext::Ext {
+        &mut self.ext
+    }
+
+    fn lines_mut(&mut self) -> &mut crate::draw::Lines {
+        &mut self.lines
+    }
 }
 
 impl crate::Textmode for Output {}
@@ -101,24 +192,90 @@ impl Output {
         Ok(self_)
     }
 
+    /// Creates a new `Output` instance which draws to the given terminal
+    /// instead of `stdout`, containing a [`ScreenGuard`](ScreenGuard)
+    /// instance for that terminal. This can be used to draw to a pty, or to
+    /// keep `stdout` free for other data.
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to duplicate the file descriptor
+    /// * `Error::WriteStdout`: failed to write initialization to the
+    ///   terminal
+    pub async fn from_fd(
+        fd: impl std::os::fd::AsFd,
+    ) -> crate::error::Result<Self> {
+        let tty: std::fs::File = fd
+            .as_fd()
+            .try_clone_to_owned()
+            .map_err(crate::error::Error::OpenTerminal)?
+            .into();
+        let screen = ScreenGuard::from_fd(&tty).await?;
+        let mut self_ =
+            Self::new_for_sink(Sink::Tty(tokio::fs::File::from_std(tty)));
+        self_.screen = Some(screen);
+        Ok(self_)
+    }
+
+    /// Creates a new `Output` instance which draws to the controlling
+    /// terminal (`/dev/tty`) instead of `stdout`. See
+    /// [`from_fd`](Output::from_fd).
+    ///
+    /// # Errors
+    /// * `Error::OpenTerminal`: failed to open `/dev/tty`
+    /// * `Error::WriteStdout`: failed to write initialization to the
+    ///   terminal
+    pub async fn from_tty() -> crate::error::Result<Self> {
+        Self::from_fd(crate::blocking::output::open_tty()?).await
+    }
+
+    /// Creates a new `Output` instance which writes to the given writer,
+    /// without creating a [`ScreenGuard`](ScreenGuard) instance. Since the
+    /// size of the terminal can't be determined from an arbitrary writer,
+    /// the in-memory screen starts out as 24x80, and should be resized with
+    /// [`set_size`](crate::Textmode::set_size) if necessary.
+    #[must_use]
+    pub fn from_writer(
+        writer: impl tokio::io::AsyncWrite + Unpin + Send + 'static,
+    ) -> Self {
+        Self::new_for_sink(Sink::Writer(Box::new(writer)))
+    }
+
     /// Creates a new `Output` instance without creating a
     /// [`ScreenGuard`](ScreenGuard) instance.
     #[must_use]
     pub fn new_without_screen() -> Self {
-        let (rows, cols) = match terminal_size::terminal_size() {
-            Some((terminal_size::Width(w), terminal_size::Height(h))) => {
-                (h, w)
-            }
-            _ => (24, 80),
-        };
+        Self::new_for_sink(Sink::Stdout(tokio::io::stdout()))
+    }
+
+    /// Creates a new `Output` instance which draws inline on `stdout`. See
+    /// [`inline`](Output::inline).
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write initialization to stdout
+    pub async fn new_inline(
+        rows: u16,
+        cleanup: crate::InlineCleanup,
+    ) -> crate::error::Result<Self> {
+        let mut self_ = Self::new_without_screen();
+        self_.inline(rows, cleanup).await?;
+        Ok(self_)
+    }
+
+    fn new_for_sink(sink: Sink) -> Self {
+        let (rows, cols) = sink.size().unwrap_or((24, 80));
         let cur = vt100::Parser::new(rows, cols, 0);
         let next = vt100::Parser::new(rows, cols, 0);
 
         Self {
-            stdout: tokio::io::stdout(),
+            sink,
             screen: None,
+            resize: None,
+            color_depth: crate::ColorDepth::detect(),
+            inline: None,
             cur,
             next,
+            ext: crate::ext::Ext::default(),
+            lines: crate::draw::Lines::default(),
         }
     }
 
@@ -130,6 +287,112 @@ impl Output {
         self.screen.take()
     }
 
+    /// Enables bracketed paste mode. This is done through the
+    /// [`ScreenGuard`](ScreenGuard) instance stored in this `Output`
+    /// instance, and so does nothing if there isn't one; in that case, call
+    /// [`ScreenGuard::enable_bracketed_paste`] directly instead.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write the escape sequence to stdout
+    pub async fn enable_bracketed_paste(
+        &mut self,
+    ) -> crate::error::Result<()> {
+        if let Some(screen) = &mut self.screen {
+            screen.enable_bracketed_paste().await?;
+        }
+        Ok(())
+    }
+
+    /// Sets whether this `Output` should automatically handle the terminal
+    /// being resized. When enabled, the next call to
+    /// [`refresh`](Output::refresh) or
+    /// [`hard_refresh`](Output::hard_refresh) after a `SIGWINCH` is received
+    /// will update the size of the in-memory screen to match the terminal
+    /// and redraw the entire screen. Defaults to false.
+    ///
+    /// # Errors
+    /// * `Error::RegisterSignal`: failed to register the signal handler
+    pub fn auto_resize(&mut self, enable: bool) -> crate::error::Result<()> {
+        if !enable {
+            self.resize = None;
+        } else if self.resize.is_none() {
+            self.resize = Some(crate::resize::ResizeFlag::new()?);
+        }
+        Ok(())
+    }
+
+    /// Sets the color depth of the terminal. Colors in the in-memory screen
+    /// which the terminal can't display are converted to the closest color
+    /// it can display when the screen is drawn to the terminal. Defaults to
+    /// the result of [`ColorDepth::detect`](crate::ColorDepth::detect).
+    pub fn color_depth(&mut self, depth: crate::ColorDepth) {
+        self.color_depth = depth;
+    }
+
+    /// Switches this `Output` to drawing inline, rather than over the whole
+    /// terminal. This reserves `rows` lines starting at the line the cursor
+    /// is currently on (scrolling the terminal up if there aren't enough
+    /// lines below the cursor), and resizes the in-memory screen to cover
+    /// just those lines. Since this doesn't use the alternate screen, this
+    /// should be used on an `Output` without a [`ScreenGuard`](ScreenGuard).
+    ///
+    /// When the `Output` is cleaned up (either explicitly with
+    /// [`cleanup`](Output::cleanup) or when it is dropped), the region is
+    /// either left in place or erased, depending on `cleanup`. Note that
+    /// cleaning up on drop isn't possible for an `Output` created with
+    /// [`from_writer`](Output::from_writer), and it may block, due to
+    /// Rust's current lack of an async drop mechanism.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write initialization to stdout
+    pub async fn inline(
+        &mut self,
+        rows: u16,
+        cleanup: crate::InlineCleanup,
+    ) -> crate::error::Result<()> {
+        let (term_rows, cols) = self.sink.size().unwrap_or_else(|| {
+            let (rows, cols) = self.cur().screen().size();
+            (rows.max(1), cols)
+        });
+        let rows = rows.clamp(1, term_rows);
+        self.sink.write(&crate::inline::init_sequence(rows)).await?;
+        self.set_size(rows, cols);
+        // the region is blank with the cursor at its start, regardless of
+        // what was drawn before
+        self.cur = vt100::Parser::new(rows, cols, 0);
+        self.inline = Some(cleanup);
+        Ok(())
+    }
+
+    /// Cleans up the terminal early: the inline region is left in place or
+    /// erased (if [`inline`](Output::inline) was used), and the
+    /// [`ScreenGuard`](ScreenGuard) (if any) is cleaned up. The in-memory
+    /// screen is left as it is (including the size it had while drawing
+    /// inline), and further calls to [`refresh`](Output::refresh) will draw
+    /// it using absolute cursor positions, from the top left of the
+    /// terminal.
+    ///
+    /// # Errors
+    /// * `Error::WriteStdout`: failed to write deinitialization to stdout
+    pub async fn cleanup(&mut self) -> crate::error::Result<()> {
+        if let Some(deinit) = self.inline_deinit_sequence() {
+            self.inline = None;
+            self.sink.write(&deinit).await?;
+        }
+        if let Some(screen) = &mut self.screen {
+            screen.cleanup().await?;
+        }
+        Ok(())
+    }
+
+    fn inline_deinit_sequence(&self) -> Option<Vec<u8>> {
+        self.inline.map(|cleanup| {
+            let (row, _) = self.cur().screen().cursor_position();
+            let (rows, _) = self.cur().screen().size();
+            crate::inline::deinit_sequence(cleanup, row, rows)
+        })
+    }
+
     /// Draws the in-memory screen to the terminal on `stdout`. This is done
     /// using a diff mechanism to only update the parts of the terminal which
     /// are different from the in-memory screen.
@@ -137,10 +400,14 @@ impl Output {
     /// # Errors
     /// * `Error::WriteStdout`: failed to write screen state to stdout
     pub async fn refresh(&mut self) -> crate::error::Result<()> {
+        if self.check_resize() {
+            return self.hard_refresh().await;
+        }
         let diff = self.next().screen().state_diff(self.cur().screen());
-        write_stdout(&mut self.stdout, &diff).await?;
-        self.cur_mut().process(&diff);
-        Ok(())
+        let diff =
+            self.ext
+                .draw(diff, self.next.screen(), self.cur.screen(), false);
+        self.write_screen_state(&diff).await
     }
 
     /// Draws the in-memory screen to the terminal on `stdout`. This clears
@@ -152,15 +419,143 @@ impl Output {
     /// # Errors
     /// * `Error::WriteStdout`: failed to write screen state to stdout
     pub async fn hard_refresh(&mut self) -> crate::error::Result<()> {
+        self.check_resize();
         let contents = self.next().screen().state_formatted();
-        write_stdout(&mut self.stdout, &contents).await?;
-        self.cur_mut().process(&contents);
-        Ok(())
+        let contents = self.ext.draw(
+            contents,
+            self.next.screen(),
+            self.cur.screen(),
+            true,
+        );
+        self.write_screen_state(&contents).await
+    }
+
+    // writes directly to the terminal, bypassing the in-memory screen
+    pub(crate) async fn write_raw(
+        &mut self,
+        buf: &[u8],
+    ) -> crate::error::Result<()> {
+        self.sink.write(buf).await
+    }
+
+    async fn write_screen_state(
+        &mut self,
+        buf: &[u8],
+    ) -> crate::error::Result<()> {
+        if self.inline.is_some() {
+            let buf = crate::inline::translate(buf, self.cur_mut());
+            self.sink
+                .write(&crate::color_depth::downgrade(&buf, self.color_depth))
+                .await
+        } else {
+            self.sink
+                .write(&crate::color_depth::downgrade(buf, self.color_depth))
+                .await?;
+            self.cur_mut().process(buf);
+            Ok(())
+        }
+    }
+
+    fn check_resize(&mut self) -> bool {
+        if self
+            .resize
+            .as_ref()
+            .is_some_and(crate::resize::ResizeFlag::take)
+        {
+            if let Some((rows, cols)) = self.sink.size() {
+                // inline regions keep their height, since the lines around
+                // them belong to someone else
+                let rows = if self.inline.is_some() {
+                    self.cur().screen().size().0.min(rows)
+                } else {
+                    rows
+                };
+                self.set_size(rows, cols);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Drop for Output {
+    /// Calls `cleanup`. Note that this may block, due to Rust's current lack
+    /// of an async drop mechanism. If this could be a problem, you should
+    /// call `cleanup` manually instead.
+    fn drop(&mut self) {
+        // doesn't literally call `cleanup`, for the same reasons as
+        // ScreenGuard (which cleans itself up when it is dropped)
+        use std::io::Write as _;
+
+        let Some(deinit) = self.inline_deinit_sequence() else {
+            return;
+        };
+        match &self.sink {
+            Sink::Stdout(_) => {
+                let mut stdout = std::io::stdout();
+                let _ = stdout.write_all(&deinit);
+                let _ = stdout.flush();
+            }
+            Sink::Tty(tty) => {
+                use std::os::fd::AsFd as _;
+
+                if let Ok(tty) = tty.as_fd().try_clone_to_owned() {
+                    let _ = crate::blocking::output::write_tty(
+                        &tty.into(),
+                        &deinit,
+                    );
+                }
+            }
+            Sink::Writer(_) => {}
+        }
+    }
+}
+
+enum Sink {
+    Stdout(tokio::io::Stdout),
+    Tty(tokio::fs::File),
+    Writer(Box<dyn tokio::io::AsyncWrite + Unpin + Send>),
+}
+
+impl Sink {
+    async fn write(&mut self, buf: &[u8]) -> crate::error::Result<()> {
+        match self {
+            Self::Stdout(stdout) => write_stdout(stdout, buf).await,
+            Self::Tty(tty) => write_stdout(tty, buf).await,
+            Self::Writer(writer) => write_stdout(writer, buf).await,
+        }
+    }
+
+    fn size(&self) -> Option<(u16, u16)> {
+        match self {
+            Self::Stdout(_) => Some(crate::terminal_size()),
+            Self::Tty(tty) => crate::terminal_size_of(tty),
+            Self::Writer(_) => None,
+        }
+    }
+}
+
+// spawn_blocking is uncancellable, and write_tty can't panic, so unwrap is
+// safe here
+pub async fn write_terminal(
+    tty: Option<&std::sync::Arc<std::fs::File>>,
+    buf: &[u8],
+) -> crate::error::Result<()> {
+    if let Some(tty) = tty {
+        let tty = std::sync::Arc::clone(tty);
+        let buf = buf.to_vec();
+        tokio::task::spawn_blocking(move || {
+            crate::blocking::output::write_tty(&tty, &buf)
+        })
+        .await
+        .unwrap()
+    } else {
+        write_stdout(&mut tokio::io::stdout(), buf).await
     }
 }
 
-async fn write_stdout(
-    stdout: &mut tokio::io::Stdout,
+pub async fn write_stdout(
+    stdout: &mut (impl tokio::io::AsyncWrite + Unpin + ?Sized),
     buf: &[u8],
 ) -> crate::error::Result<()> {
     stdout
@@ -3,15 +3,74 @@ pub trait Output {
     fn cur_mut(&mut self) -> &mut vt100::Parser;
     fn next(&self) -> &vt100::Parser;
     fn next_mut(&mut self) -> &mut vt100::Parser;
+    fn ext(&self) -> &crate::ext::Ext;
+    fn ext_mut(&mut self) -> &mut crate::ext::Ext;
+    fn lines_mut(&mut self) -> &mut crate::draw::Lines;
+
+    fn process(&mut self, buf: &[u8]) {
+        if self.ext().needs_mirror(buf) {
+            let screen = self.next().screen();
+            let (size, contents) =
+                (screen.size(), screen.contents_formatted());
+            self.ext_mut().start(size, &contents);
+        }
+        match self.ext_mut().process(buf) {
+            Some(buf) => self.next_mut().process(&buf),
+            None => self.next_mut().process(buf),
+        }
+    }
 
     fn write_u16(&mut self, i: u16) {
         let mut itoa_buf = itoa::Buffer::new();
-        self.next_mut().process(itoa_buf.format(i).as_bytes());
+        self.process(itoa_buf.format(i).as_bytes());
     }
 
     fn write_u8(&mut self, i: u8) {
         let mut itoa_buf = itoa::Buffer::new();
-        self.next_mut().process(itoa_buf.format(i).as_bytes());
+        self.process(itoa_buf.format(i).as_bytes());
+    }
+}
+
+// the primitives that the helpers in crate::Draw are built on
+pub trait Draw {
+    type Target: crate::Textmode;
+
+    // the output being drawn to, and the region of it that drawing is
+    // restricted to. the other positions here are relative to that region.
+    fn target(&mut self) -> (&mut Self::Target, crate::draw::Clip);
+    fn cursor(&self) -> (u16, u16);
+    fn goto(&mut self, row: u16, col: u16);
+    fn put_str(&mut self, text: &str);
+    fn style(&self) -> crate::Style;
+    fn put_style(&mut self, style: crate::Style);
+}
+
+impl<T: crate::Textmode> Draw for T {
+    type Target = Self;
+
+    fn target(&mut self) -> (&mut Self, crate::draw::Clip) {
+        let clip = crate::draw::Clip::screen(self.screen());
+        (self, clip)
+    }
+
+    fn cursor(&self) -> (u16, u16) {
+        self.screen().cursor_position()
+    }
+
+    fn goto(&mut self, row: u16, col: u16) {
+        self.move_to(row, col);
+    }
+
+    fn put_str(&mut self, text: &str) {
+        self.write_str(text);
+    }
+
+    fn style(&self) -> crate::Style {
+        self.current_style()
+    }
+
+    fn put_style(&mut self, style: crate::Style) {
+        self.set_style(style);
     }
 }
 
@@ -28,6 +87,7 @@ pub trait Input {
     fn should_parse_ctrl(&self) -> bool;
     fn should_parse_meta(&self) -> bool;
     fn should_parse_special_keys(&self) -> bool;
+    fn terminfo_keys(&self) -> Option<&crate::TerminfoKeys>;
 
     fn try_read_string(&mut self) -> Option<crate::Key> {
         if !self.should_parse_utf8() {
@@ -120,7 +180,8 @@ pub trait Input {
                 if self.should_parse_meta()
                     || self.should_parse_special_keys()
                 {
-                    self.read_escape_sequence()
+                    self.read_terminfo_key()
+                        .or_else(|| self.read_escape_sequence())
                 } else {
                     Some(crate::Key::Byte(27))
                 }
@@ -151,6 +212,16 @@ pub trait Input {
         }
     }
 
+    // the escape has already been consumed here
+    fn read_terminfo_key(&mut self) -> Option<crate::Key> {
+        if !self.should_parse_special_keys() {
+            return None;
+        }
+        let (len, key) = self.terminfo_keys()?.find(self.buf())?;
+        self.consume(len);
+        Some(key)
+    }
+
     fn read_escape_sequence(&mut self) -> Option<crate::Key> {
         enum EscapeState {
             Escape,
@@ -211,36 +282,109 @@ pub trait Input {
                     _ => fail!(),
                 },
                 EscapeState::Csi(ref mut param) => match c {
-                    b'A' => return Some(crate::Key::Up),
-                    b'B' => return Some(crate::Key::Down),
-                    b'C' => return Some(crate::Key::Right),
-                    b'D' => return Some(crate::Key::Left),
-                    b'H' => return Some(crate::Key::Home),
-                    b'F' => return Some(crate::Key::End),
-                    b'0'..=b'9' => param.push(c),
-                    b'~' => match param.as_slice() {
-                        [b'2'] => return Some(crate::Key::Insert),
-                        [b'3'] => return Some(crate::Key::Delete),
-                        [b'5'] => return Some(crate::Key::PageUp),
-                        [b'6'] => return Some(crate::Key::PageDown),
-                        [b'1', b'5'] => return Some(crate::Key::F(5)),
-                        [b'1', b'7'] => return Some(crate::Key::F(6)),
-                        [b'1', b'8'] => return Some(crate::Key::F(7)),
-                        [b'1', b'9'] => return Some(crate::Key::F(8)),
-                        [b'2', b'0'] => return Some(crate::Key::F(9)),
-                        [b'2', b'1'] => return Some(crate::Key::F(10)),
-                        [b'2', b'3'] => return Some(crate::Key::F(11)),
-                        [b'2', b'4'] => return Some(crate::Key::F(12)),
-                        [b'2', b'5'] => return Some(crate::Key::F(13)),
-                        [b'2', b'6'] => return Some(crate::Key::F(14)),
-                        [b'2', b'8'] => return Some(crate::Key::F(15)),
-                        [b'2', b'9'] => return Some(crate::Key::F(16)),
-                        [b'3', b'1'] => return Some(crate::Key::F(17)),
-                        [b'3', b'2'] => return Some(crate::Key::F(18)),
-                        [b'3', b'3'] => return Some(crate::Key::F(19)),
-                        [b'3', b'4'] => return Some(crate::Key::F(20)),
-                        _ => fail!(),
-                    },
+                    b'0'..=b'9' | b';' | b':' => param.push(c),
+                    b'<' if param.is_empty() => param.push(c),
+                    b'I' if param.is_empty() => {
+                        return Some(crate::Key::FocusGained)
+                    }
+                    b'O' if param.is_empty() => {
+                        return Some(crate::Key::FocusLost)
+                    }
+                    b'Z' if param.is_empty() => {
+                        return Some(crate::Key::BackTab)
+                    }
+                    b'M' | b'm' if param.first() == Some(&b'<') => {
+                        let Some(params) = parse_csi_params(&param[1..])
+                        else {
+                            fail!()
+                        };
+                        let [code, col, row] = params.as_slice() else {
+                            fail!()
+                        };
+                        let Some(event) = crate::MouseEvent::from_sgr_params(
+                            code[0],
+                            col[0],
+                            row[0],
+                            c == b'm',
+                        ) else {
+                            fail!()
+                        };
+                        return Some(crate::Key::Mouse(event));
+                    }
+                    b'A' | b'B' | b'C' | b'D' | b'H' | b'F' | b'P' | b'Q'
+                    | b'R' | b'S' => {
+                        let Some(params) = parse_csi_params(param) else {
+                            fail!()
+                        };
+                        let key = match c {
+                            b'A' => crate::Key::Up,
+                            b'B' => crate::Key::Down,
+                            b'C' => crate::Key::Right,
+                            b'D' => crate::Key::Left,
+                            b'H' => crate::Key::Home,
+                            b'F' => crate::Key::End,
+                            // a bare CSI P/Q/R/S isn't a key, only the
+                            // modified forms of F1-F4 are
+                            _ if params.len() < 2 => fail!(),
+                            b'P' => crate::Key::F(1),
+                            b'Q' => crate::Key::F(2),
+                            b'R' => crate::Key::F(3),
+                            b'S' => crate::Key::F(4),
+                            _ => unreachable!(),
+                        };
+                        let Some(key) = with_modifiers(key, &params) else {
+                            fail!()
+                        };
+                        return Some(key);
+                    }
+                    b'~' => {
+                        let Some(params) = parse_csi_params(param) else {
+                            fail!()
+                        };
+                        let key = match params.first().map(|p| p[0]) {
+                            Some(200) if params.len() == 1 => {
+                                return Some(self.read_paste());
+                            }
+                            Some(2) => crate::Key::Insert,
+                            Some(3) => crate::Key::Delete,
+                            Some(5) => crate::Key::PageUp,
+                            Some(6) => crate::Key::PageDown,
+                            Some(n) => {
+                                let Some(key) =
+                                    crate::Key::from_f_key_param(n)
+                                else {
+                                    fail!()
+                                };
+                                key
+                            }
+                            None => fail!(),
+                        };
+                        let Some(key) = with_modifiers(key, &params) else {
+                            fail!()
+                        };
+                        return Some(key);
+                    }
+                    b'u' => {
+                        let Some(params) = parse_csi_params(param) else {
+                            fail!()
+                        };
+                        let Some(code) = params.first().map(|p| p[0]) else {
+                            fail!()
+                        };
+                        let Some(key) = crate::Key::from_kitty_code(code)
+                        else {
+                            // keys with no equivalent are passed through
+                            // as is, rather than being parsed as text
+                            return Some(crate::Key::Bytes(seen));
+                        };
+                        let Some(event) = crate::KeyEvent::from_params(
+                            key,
+                            params.get(1).map(Vec::as_slice),
+                        ) else {
+                            fail!()
+                        };
+                        return Some(crate::Key::Event(Box::new(event)));
+                    }
                     _ => fail!(),
                 },
                 EscapeState::Ckm => match c {
@@ -258,6 +402,76 @@ pub trait Input {
         }
     }
 
+    // the paste start sequence has already been consumed here. fill_buf is
+    // responsible for making sure the entire paste is already in the buffer
+    // (see paste_is_incomplete), so if the end sequence is missing, it means
+    // we hit eof.
+    fn read_paste(&mut self) -> crate::Key {
+        let buf = self.buf();
+        let (len, consumed) = find_subslice(buf, crate::PASTE_END)
+            .map_or((buf.len(), buf.len()), |i| {
+                (i, i + crate::PASTE_END.len())
+            });
+        let text =
+            std::string::String::from_utf8_lossy(&buf[..len]).into_owned();
+        self.consume(consumed);
+        crate::Key::Paste(text)
+    }
+
+    fn paste_is_incomplete(&self) -> bool {
+        self.should_parse_special_keys()
+            && self.buf().starts_with(crate::PASTE_START)
+            && find_subslice(
+                &self.buf()[crate::PASTE_START.len()..],
+                crate::PASTE_END,
+            )
+            .is_none()
+    }
+
+    // returns true if the buffer starts with something that could be the
+    // beginning of an escape sequence that we would parse, but more bytes
+    // are needed to tell
+    fn escape_sequence_is_incomplete(&self) -> bool {
+        if !self.should_parse_meta() && !self.should_parse_special_keys() {
+            return false;
+        }
+        if self.should_parse_special_keys() {
+            if let (Some(keys), [b'\x1b', rest @ ..]) =
+                (self.terminfo_keys(), self.buf())
+            {
+                if keys.is_prefix(rest) {
+                    return true;
+                }
+            }
+        }
+        match self.buf() {
+            [b'\x1b'] => true,
+            [b'\x1b', b'O'] => self.should_parse_special_keys(),
+            [b'\x1b', b'[', rest @ ..] => {
+                self.should_parse_special_keys()
+                    && rest.iter().all(|c| {
+                        matches!(c, b'0'..=b'9' | b';' | b':' | b'<')
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    // removes the first reply of the form `CSI <params> <final_byte>` with
+    // exactly `nparams` parameters from anywhere in the unread part of the
+    // buffer, leaving the bytes around it in place to be read as keys
+    fn take_csi_reply(
+        &mut self,
+        final_byte: u8,
+        nparams: usize,
+    ) -> Option<Vec<u32>> {
+        let (start, end, params) =
+            find_csi_reply(self.buf(), final_byte, nparams)?;
+        let offset = self.buf_mut_vec().len() - self.buf().len();
+        self.buf_mut_vec().drain(offset + start..offset + end);
+        Some(params)
+    }
+
     fn read_utf8_char(&mut self, initial: u8) -> Option<crate::Key> {
         let mut buf = vec![initial];
 
@@ -342,3 +556,77 @@ pub trait Input {
         }
     }
 }
+
+// parses the numeric parameters (and colon separated subparameters) of a
+// csi sequence, where missing values default to 1
+fn parse_csi_params(param: &[u8]) -> Option<Vec<Vec<u32>>> {
+    if param.is_empty() {
+        return Some(vec![]);
+    }
+    param
+        .split(|&c| c == b';')
+        .map(|param| {
+            param
+                .split(|&c| c == b':')
+                .map(|n| {
+                    if n.is_empty() {
+                        return Some(1);
+                    }
+                    std::str::from_utf8(n).ok()?.parse().ok()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn with_modifiers(
+    key: crate::Key,
+    params: &[Vec<u32>],
+) -> Option<crate::Key> {
+    match params {
+        [] | [_] => Some(key),
+        // an explicit event type is only sent by the kitty keyboard
+        // protocol
+        [_, modifiers] if modifiers.len() > 1 => Some(crate::Key::Event(
+            Box::new(crate::KeyEvent::from_params(key, Some(modifiers))?),
+        )),
+        [_, modifiers] => {
+            let modifiers = crate::Modifiers::from_xterm_param(modifiers[0])?;
+            if modifiers.is_empty() {
+                Some(key)
+            } else {
+                Some(crate::Key::Modified(Box::new(key), modifiers))
+            }
+        }
+        _ => None,
+    }
+}
+
+fn find_csi_reply(
+    buf: &[u8],
+    final_byte: u8,
+    nparams: usize,
+) -> Option<(usize, usize, Vec<u32>)> {
+    (0..buf.len()).find_map(|start| {
+        let rest = buf[start..].strip_prefix(b"\x1b[")?;
+        let len =
+            rest.iter().position(|c| !matches!(c, b'0'..=b'9' | b';'))?;
+        if rest[len] != final_byte {
+            return None;
+        }
+        let params = rest[..len]
+            .split(|&c| c == b';')
+            .map(|n| std::str::from_utf8(n).ok()?.parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        if params.len() != nparams {
+            return None;
+        }
+        Some((start, start + 2 + len + 1, params))
+    })
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
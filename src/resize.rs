@@ -0,0 +1,64 @@
+// tracks whether a SIGWINCH has been received since the last check, for
+// automatically resizing an Output
+pub struct ResizeFlag {
+    flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    id: signal_hook::SigId,
+}
+
+impl ResizeFlag {
+    pub fn new() -> crate::error::Result<Self> {
+        let flag =
+            std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let id = signal_hook::flag::register(
+            signal_hook::consts::SIGWINCH,
+            std::sync::Arc::clone(&flag),
+        )
+        .map_err(crate::error::Error::RegisterSignal)?;
+        Ok(Self { flag, id })
+    }
+
+    pub fn take(&self) -> bool {
+        self.flag.swap(false, std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+impl Drop for ResizeFlag {
+    fn drop(&mut self) {
+        signal_hook::low_level::unregister(self.id);
+    }
+}
+
+/// Watches for the terminal being resized.
+///
+/// This listens for `SIGWINCH`, and reports the new size of the terminal on
+/// `stdout` each time it is received. Note that if you just want the
+/// [`Output`](crate::Output) to update itself, you can use
+/// [`auto_resize`](crate::Output::auto_resize) instead.
+#[cfg(feature = "async")]
+pub struct ResizeWatcher {
+    signal: tokio::signal::unix::Signal,
+}
+
+#[cfg(feature = "async")]
+impl ResizeWatcher {
+    /// Starts listening for `SIGWINCH`. This must be called from within a
+    /// tokio runtime.
+    ///
+    /// # Errors
+    /// * `Error::RegisterSignal`: failed to register the signal handler
+    pub fn new() -> crate::error::Result<Self> {
+        let signal = tokio::signal::unix::signal(
+            tokio::signal::unix::SignalKind::window_change(),
+        )
+        .map_err(crate::error::Error::RegisterSignal)?;
+        Ok(Self { signal })
+    }
+
+    /// Waits for the terminal to be resized, and returns the new size as
+    /// `(rows, cols)`. Multiple resizes which happen before this is called
+    /// may be combined into a single notification. Returns `None` if no more
+    /// notifications can be received.
+    pub async fn recv(&mut self) -> Option<(u16, u16)> {
+        self.signal.recv().await.map(|()| crate::terminal_size())
+    }
+}
@@ -0,0 +1,98 @@
+/// A copy of the contents of an in-memory screen, which can be restored
+/// later with [`restore_snapshot`](crate::Textmode::restore_snapshot).
+///
+/// With the `serde` feature enabled, this can be serialized, which allows
+/// things like saving and replaying sessions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// The size of the screen, as `(rows, cols)`.
+    pub size: (u16, u16),
+    /// The cells of the screen, one `Vec` per row.
+    pub cells: Vec<Vec<SnapshotCell>>,
+    /// The position of the cursor, as `(row, col)`.
+    pub cursor_position: (u16, u16),
+    /// Whether the cursor is hidden.
+    pub hide_cursor: bool,
+    /// The drawing attributes which were active.
+    pub attrs: crate::Style,
+}
+
+/// A single cell of a [`Snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotCell {
+    /// The text contained in the cell (empty if nothing has been drawn
+    /// there).
+    pub contents: String,
+    /// Whether the cell contains a wide character, which also takes up the
+    /// following cell.
+    pub wide: bool,
+    /// The attributes the cell was drawn with.
+    pub attrs: crate::Style,
+}
+
+impl Snapshot {
+    pub(crate) fn new(screen: &vt100::Screen, ext: &crate::ext::Ext) -> Self {
+        let (rows, cols) = screen.size();
+        let cells = (0..rows)
+            .map(|row| {
+                (0..cols)
+                    .map(|col| {
+                        screen.cell(row, col).map_or_else(
+                            SnapshotCell::default,
+                            |cell| SnapshotCell {
+                                contents: cell.contents(),
+                                wide: cell.is_wide(),
+                                attrs: crate::Style::from_cell(
+                                    cell,
+                                    ext.cell_attrs(row, col),
+                                ),
+                            },
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            size: (rows, cols),
+            cells,
+            cursor_position: screen.cursor_position(),
+            hide_cursor: screen.hide_cursor(),
+            attrs: crate::Style::from_screen(screen, ext.attrs()),
+        }
+    }
+
+    pub(crate) fn restore<T: crate::Textmode + ?Sized>(&self, tm: &mut T) {
+        tm.set_size(self.size.0, self.size.1);
+        tm.reset_attributes();
+        tm.clear();
+
+        for (row, cells) in (0..).zip(&self.cells) {
+            let mut cells = (0..).zip(cells);
+            while let Some((col, cell)) = cells.next() {
+                if cell.contents.is_empty()
+                    && cell.attrs == crate::Style::default()
+                {
+                    continue;
+                }
+                tm.move_to(row, col);
+                tm.set_style(cell.attrs);
+                if cell.contents.is_empty() {
+                    // erase the cell, so that it gets the background color
+                    // without any contents
+                    tm.write(b"\x1b[X");
+                } else {
+                    tm.write_str(&cell.contents);
+                }
+                if cell.wide {
+                    cells.next();
+                }
+            }
+        }
+
+        tm.set_style(self.attrs);
+        tm.move_to(self.cursor_position.0, self.cursor_position.1);
+        tm.hide_cursor(self.hide_cursor);
+    }
+}
@@ -0,0 +1,436 @@
+/// A set of drawing attributes, which can be applied all at once with
+/// [`set_style`](crate::Textmode::set_style).
+///
+/// The default style is the terminal's default colors with no other
+/// attributes set.
+///
+/// ```
+/// let style = textmode::Style::new()
+///     .fgcolor(textmode::color::RED)
+///     .bold(true);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Style {
+    /// The foreground color.
+    #[cfg_attr(feature = "serde", serde(with = "crate::color::serde"))]
+    pub fgcolor: crate::Color,
+    /// The background color.
+    #[cfg_attr(feature = "serde", serde(with = "crate::color::serde"))]
+    pub bgcolor: crate::Color,
+    /// Whether text is bold.
+    pub bold: bool,
+    /// Whether text is italic.
+    pub italic: bool,
+    /// Whether text is underlined.
+    pub underline: bool,
+    /// Whether text has its colors inverted.
+    pub inverse: bool,
+    /// Whether text is drawn dimmer than usual.
+    pub dim: bool,
+    /// Whether text blinks.
+    pub blink: bool,
+    /// Whether text is hidden.
+    pub hidden: bool,
+    /// Whether text is struck through.
+    pub strikethrough: bool,
+    /// Whether text has a line drawn over it.
+    pub overline: bool,
+    /// The kind of underline to draw, if `underline` is set.
+    pub underline_style: UnderlineStyle,
+    /// The color of the underline. The default color draws the underline
+    /// in the same color as the text.
+    #[cfg_attr(feature = "serde", serde(with = "crate::color::serde"))]
+    pub underline_color: crate::Color,
+}
+
+/// The kinds of underline which can be drawn by terminals which support
+/// them. Terminals which don't will generally draw a single underline
+/// instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnderlineStyle {
+    /// A single straight line.
+    #[default]
+    Single,
+    /// Two straight lines.
+    Double,
+    /// A wavy line.
+    Curly,
+    /// A dotted line.
+    Dotted,
+    /// A dashed line.
+    Dashed,
+}
+
+impl UnderlineStyle {
+    pub(crate) fn from_param(param: u8) -> Option<Self> {
+        match param {
+            1 => Some(Self::Single),
+            2 => Some(Self::Double),
+            3 => Some(Self::Curly),
+            4 => Some(Self::Dotted),
+            5 => Some(Self::Dashed),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn param(self) -> u8 {
+        match self {
+            Self::Single => 1,
+            Self::Double => 2,
+            Self::Curly => 3,
+            Self::Dotted => 4,
+            Self::Dashed => 5,
+        }
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Style {
+    /// Returns the default style.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            fgcolor: crate::Color::Default,
+            bgcolor: crate::Color::Default,
+            bold: false,
+            italic: false,
+            underline: false,
+            inverse: false,
+            dim: false,
+            blink: false,
+            hidden: false,
+            strikethrough: false,
+            overline: false,
+            underline_style: UnderlineStyle::Single,
+            underline_color: crate::Color::Default,
+        }
+    }
+
+    /// Sets the foreground color.
+    #[must_use]
+    pub const fn fgcolor(mut self, color: crate::Color) -> Self {
+        self.fgcolor = color;
+        self
+    }
+
+    /// Sets the background color.
+    #[must_use]
+    pub const fn bgcolor(mut self, color: crate::Color) -> Self {
+        self.bgcolor = color;
+        self
+    }
+
+    /// Sets whether text should be bold.
+    #[must_use]
+    pub const fn bold(mut self, bold: bool) -> Self {
+        self.bold = bold;
+        self
+    }
+
+    /// Sets whether text should be italic.
+    #[must_use]
+    pub const fn italic(mut self, italic: bool) -> Self {
+        self.italic = italic;
+        self
+    }
+
+    /// Sets whether text should be underlined.
+    #[must_use]
+    pub const fn underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+        self
+    }
+
+    /// Sets whether text should have its colors inverted.
+    #[must_use]
+    pub const fn inverse(mut self, inverse: bool) -> Self {
+        self.inverse = inverse;
+        self
+    }
+
+    /// Sets whether text should be drawn dimmer than usual.
+    #[must_use]
+    pub const fn dim(mut self, dim: bool) -> Self {
+        self.dim = dim;
+        self
+    }
+
+    /// Sets whether text should blink.
+    #[must_use]
+    pub const fn blink(mut self, blink: bool) -> Self {
+        self.blink = blink;
+        self
+    }
+
+    /// Sets whether text should be hidden.
+    #[must_use]
+    pub const fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Sets whether text should be struck through.
+    #[must_use]
+    pub const fn strikethrough(mut self, strikethrough: bool) -> Self {
+        self.strikethrough = strikethrough;
+        self
+    }
+
+    /// Sets whether text should have a line drawn over it.
+    #[must_use]
+    pub const fn overline(mut self, overline: bool) -> Self {
+        self.overline = overline;
+        self
+    }
+
+    /// Sets text to be underlined with the given kind of underline.
+    #[must_use]
+    pub const fn underline_style(mut self, style: UnderlineStyle) -> Self {
+        self.underline = true;
+        self.underline_style = style;
+        self
+    }
+
+    /// Sets the color of the underline.
+    #[must_use]
+    pub const fn underline_color(mut self, color: crate::Color) -> Self {
+        self.underline_color = color;
+        self
+    }
+
+    pub(crate) fn from_screen(
+        screen: &vt100::Screen,
+        ext: crate::ext::Attrs,
+    ) -> Self {
+        Self {
+            fgcolor: screen.fgcolor(),
+            bgcolor: screen.bgcolor(),
+            bold: screen.bold(),
+            italic: screen.italic(),
+            underline: screen.underline(),
+            inverse: screen.inverse(),
+            ..Self::from_ext(ext)
+        }
+    }
+
+    pub(crate) fn from_cell(
+        cell: &vt100::Cell,
+        ext: crate::ext::Attrs,
+    ) -> Self {
+        Self {
+            fgcolor: cell.fgcolor(),
+            bgcolor: cell.bgcolor(),
+            bold: cell.bold(),
+            italic: cell.italic(),
+            underline: cell.underline(),
+            inverse: cell.inverse(),
+            ..Self::from_ext(ext)
+        }
+    }
+
+    fn from_ext(ext: crate::ext::Attrs) -> Self {
+        Self {
+            dim: ext.dim(),
+            blink: ext.blink(),
+            hidden: ext.hidden(),
+            strikethrough: ext.strikethrough(),
+            overline: ext.overline(),
+            underline_style: ext.underline_style().unwrap_or_default(),
+            underline_color: ext.underline_color,
+            ..Self::new()
+        }
+    }
+
+    // the kind of underline to draw, if any
+    fn underline_kind(self) -> Option<UnderlineStyle> {
+        self.underline.then_some(self.underline_style)
+    }
+
+    // the number of attributes which differ between the two styles
+    fn changes_from(self, other: Self) -> usize {
+        [
+            self.fgcolor != other.fgcolor,
+            self.bgcolor != other.bgcolor,
+            self.bold != other.bold,
+            self.italic != other.italic,
+            self.underline_kind() != other.underline_kind(),
+            self.inverse != other.inverse,
+            self.dim != other.dim,
+            self.blink != other.blink,
+            self.hidden != other.hidden,
+            self.strikethrough != other.strikethrough,
+            self.overline != other.overline,
+            self.underline_color != other.underline_color,
+        ]
+        .into_iter()
+        .filter(|&changed| changed)
+        .count()
+    }
+
+    // returns an sgr sequence which resets the attributes and then sets
+    // them to this style. the underline color is given with subparameters,
+    // since vt100 would misinterpret the separate parameter form.
+    pub(crate) fn sgr(self) -> Vec<u8> {
+        let mut params = vec!["0".to_string()];
+        params.extend(color_param(self.fgcolor, 30, 38));
+        params.extend(color_param(self.bgcolor, 40, 48));
+        for (set, param) in [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italic, "3"),
+            (self.blink, "5"),
+            (self.inverse, "7"),
+            (self.hidden, "8"),
+            (self.strikethrough, "9"),
+            (self.overline, "53"),
+        ] {
+            if set {
+                params.push(param.to_string());
+            }
+        }
+        match self.underline_kind() {
+            None => {}
+            Some(UnderlineStyle::Single) => params.push("4".to_string()),
+            Some(style) => {
+                // vt100 doesn't understand the subparameter form
+                params.push("4".to_string());
+                params.push(format!("4:{}", style.param()));
+            }
+        }
+        match self.underline_color {
+            crate::Color::Default => {}
+            crate::Color::Idx(i) => params.push(format!("58:5:{i}")),
+            crate::Color::Rgb(r, g, b) => {
+                params.push(format!("58:2::{r}:{g}:{b}"));
+            }
+        }
+        format!("\x1b[{}m", params.join(";")).into_bytes()
+    }
+
+    pub(crate) fn apply<T: crate::Textmode + ?Sized>(
+        self,
+        tm: &mut T,
+        cur: Self,
+    ) {
+        if self.changes_from(cur) == 0 {
+            return;
+        }
+        // turning off several attributes one at a time takes more escape
+        // sequences than resetting everything and turning the rest back on
+        if 1 + self.changes_from(Self::new()) < self.changes_from(cur) {
+            tm.reset_attributes();
+        }
+        // the setters check the current state themselves (and bold and dim
+        // can only be turned off together), so this has to be reread as
+        // each one is applied
+        if self.fgcolor != tm.current_style().fgcolor {
+            tm.set_fgcolor(self.fgcolor);
+        }
+        if self.bgcolor != tm.current_style().bgcolor {
+            tm.set_bgcolor(self.bgcolor);
+        }
+        if self.bold != tm.current_style().bold {
+            tm.set_bold(self.bold);
+        }
+        if self.dim != tm.current_style().dim {
+            tm.set_dim(self.dim);
+        }
+        if self.italic != tm.current_style().italic {
+            tm.set_italic(self.italic);
+        }
+        if self.underline_kind() != tm.current_style().underline_kind() {
+            match self.underline_kind() {
+                None => tm.set_underline(false),
+                Some(UnderlineStyle::Single) => tm.set_underline(true),
+                Some(style) => tm.set_underline_style(style),
+            }
+        }
+        if self.inverse != tm.current_style().inverse {
+            tm.set_inverse(self.inverse);
+        }
+        if self.blink != tm.current_style().blink {
+            tm.set_blink(self.blink);
+        }
+        if self.hidden != tm.current_style().hidden {
+            tm.set_hidden(self.hidden);
+        }
+        if self.strikethrough != tm.current_style().strikethrough {
+            tm.set_strikethrough(self.strikethrough);
+        }
+        if self.overline != tm.current_style().overline {
+            tm.set_overline(self.overline);
+        }
+        if self.underline_color != tm.current_style().underline_color {
+            tm.set_underline_color(self.underline_color);
+        }
+    }
+}
+
+fn color_param(
+    color: crate::Color,
+    base: u8,
+    extended: u8,
+) -> Option<String> {
+    match color {
+        crate::Color::Default => None,
+        crate::Color::Idx(i) if i < 8 => Some(format!("{}", base + i)),
+        crate::Color::Idx(i) if i < 16 => Some(format!("{}", base + 52 + i)),
+        crate::Color::Idx(i) => Some(format!("{extended};5;{i}")),
+        crate::Color::Rgb(r, g, b) => {
+            Some(format!("{extended};2;{r};{g};{b}"))
+        }
+    }
+}
+
+/// A piece of text to be drawn with a particular [`Style`], for use with
+/// [`write_spans`](crate::Draw::write_spans).
+///
+/// A line made up of differently styled pieces of text can be represented
+/// as a slice of spans.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Span<'a> {
+    /// The text to draw.
+    pub text: std::borrow::Cow<'a, str>,
+    /// The style to draw it with.
+    pub style: Style,
+}
+
+impl<'a> Span<'a> {
+    /// Creates a span which draws `text` with `style`.
+    #[must_use]
+    pub fn new(
+        text: impl Into<std::borrow::Cow<'a, str>>,
+        style: Style,
+    ) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+
+    /// Returns the number of columns that the span's text takes up on the
+    /// screen.
+    #[must_use]
+    pub fn width(&self) -> usize {
+        crate::text::width(&self.text)
+    }
+}
+
+impl<'a> From<&'a str> for Span<'a> {
+    fn from(text: &'a str) -> Self {
+        Self::new(text, Style::new())
+    }
+}
+
+impl From<String> for Span<'_> {
+    fn from(text: String) -> Self {
+        Self::new(text, Style::new())
+    }
+}
@@ -0,0 +1,323 @@
+// the indices of the capabilities we care about, in the order they are
+// stored in compiled terminfo files (see term.h)
+const MAX_COLORS: usize = 13;
+const KEY_BACKSPACE: usize = 55;
+const KEY_DC: usize = 59;
+const KEY_DOWN: usize = 61;
+const KEY_F0: usize = 65;
+const KEY_F1: usize = 66;
+const KEY_F10: usize = 67;
+const KEY_F2: usize = 68;
+const KEY_HOME: usize = 76;
+const KEY_IC: usize = 77;
+const KEY_LEFT: usize = 79;
+const KEY_NPAGE: usize = 81;
+const KEY_PPAGE: usize = 82;
+const KEY_RIGHT: usize = 83;
+const KEY_UP: usize = 87;
+const KEY_BTAB: usize = 148;
+const KEY_END: usize = 164;
+const KEY_F11: usize = 216;
+
+const MAGIC_LEGACY: i16 = 0o432;
+const MAGIC_32BIT: i16 = 0o1036;
+
+// the parts of a compiled terminfo entry that we use
+pub struct Terminfo {
+    numbers: Vec<i32>,
+    strings: Vec<Option<Vec<u8>>>,
+    ext_booleans: std::collections::HashSet<String>,
+}
+
+impl Terminfo {
+    pub fn from_env() -> Option<Self> {
+        Self::load(&std::env::var("TERM").ok()?)
+    }
+
+    // searches the same directories that ncurses does
+    pub fn load(term: &str) -> Option<Self> {
+        let first = term.chars().next()?;
+        if term.contains('/') {
+            return None;
+        }
+        search_dirs().into_iter().find_map(|dir| {
+            [
+                dir.join(first.to_string()).join(term),
+                dir.join(format!("{:x}", u32::from(first))).join(term),
+            ]
+            .into_iter()
+            .find_map(|path| std::fs::read(path).ok())
+            .and_then(|data| Self::parse(&data))
+        })
+    }
+
+    // handles both the legacy and the 32-bit number formats
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let mut r = Reader { data, pos: 0 };
+        let number_size = match r.i16()? {
+            MAGIC_LEGACY => 2,
+            MAGIC_32BIT => 4,
+            _ => return None,
+        };
+        let names_size = r.count()?;
+        let booleans_count = r.count()?;
+        let numbers_count = r.count()?;
+        let strings_count = r.count()?;
+        let table_size = r.count()?;
+
+        r.skip(names_size)?;
+        r.skip(booleans_count)?;
+        r.align();
+        let numbers = (0..numbers_count)
+            .map(|_| r.number(number_size))
+            .collect::<Option<Vec<_>>>()?;
+        let offsets = (0..strings_count)
+            .map(|_| r.i16())
+            .collect::<Option<Vec<_>>>()?;
+        let table = r.take(table_size)?;
+        let strings = offsets
+            .iter()
+            .map(|&offset| string_at(table, offset))
+            .collect();
+
+        // older terminfo files may not have an extended section at all
+        r.align();
+        let ext_booleans =
+            parse_extended(&mut r, number_size).unwrap_or_default();
+
+        Some(Self {
+            numbers,
+            strings,
+            ext_booleans,
+        })
+    }
+
+    pub fn max_colors(&self) -> Option<i32> {
+        // negative numbers mean that the capability is absent or cancelled
+        self.numbers.get(MAX_COLORS).copied().filter(|&n| n >= 0)
+    }
+
+    // extended capabilities are things like RGB or Tc, which aren't part of
+    // the standard set
+    pub fn has_ext_boolean(&self, name: &str) -> bool {
+        self.ext_booleans.contains(name)
+    }
+
+    fn string(&self, idx: usize) -> Option<&[u8]> {
+        self.strings.get(idx)?.as_deref()
+    }
+}
+
+/// A table of the key sequences described by a terminal's terminfo entry.
+///
+/// The escape sequences sent by the cursor keys, function keys, and so on
+/// vary between terminals. By default, [`Input`](crate::blocking::Input)
+/// only recognizes the sequences sent by xterm (and terminals compatible
+/// with it), which covers most modern terminals. Setting a `TerminfoKeys`
+/// table with
+/// [`terminfo_keys`](crate::blocking::Input::terminfo_keys) allows the
+/// sequences used by other terminals (such as rxvt or the Linux console) to
+/// be recognized as well. Sequences which aren't in the table fall back to
+/// the built-in parsing.
+#[derive(Debug, Clone)]
+pub struct TerminfoKeys {
+    // sequences with the leading escape removed, longest first
+    keys: Vec<(Vec<u8>, crate::Key)>,
+}
+
+impl TerminfoKeys {
+    /// Loads the key sequences for the terminal named by `$TERM`. Returns
+    /// `None` if `$TERM` isn't set or no terminfo entry could be found for
+    /// it.
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        Terminfo::from_env().map(|terminfo| Self::new(&terminfo))
+    }
+
+    /// Loads the key sequences for the given terminal name. Terminfo
+    /// entries are searched for in `$TERMINFO`, `~/.terminfo`,
+    /// `$TERMINFO_DIRS`, and the standard system directories such as
+    /// `/usr/share/terminfo`. Returns `None` if no terminfo entry could be
+    /// found.
+    #[must_use]
+    pub fn load(term: &str) -> Option<Self> {
+        Terminfo::load(term).map(|terminfo| Self::new(&terminfo))
+    }
+
+    fn new(terminfo: &Terminfo) -> Self {
+        let mut caps = vec![
+            (KEY_BACKSPACE, crate::Key::Backspace),
+            (KEY_DC, crate::Key::Delete),
+            (KEY_DOWN, crate::Key::Down),
+            (KEY_F0, crate::Key::F(0)),
+            (KEY_F1, crate::Key::F(1)),
+            (KEY_F10, crate::Key::F(10)),
+            (KEY_HOME, crate::Key::Home),
+            (KEY_IC, crate::Key::Insert),
+            (KEY_LEFT, crate::Key::Left),
+            (KEY_NPAGE, crate::Key::PageDown),
+            (KEY_PPAGE, crate::Key::PageUp),
+            (KEY_RIGHT, crate::Key::Right),
+            (KEY_UP, crate::Key::Up),
+            (KEY_BTAB, crate::Key::BackTab),
+            (KEY_END, crate::Key::End),
+        ];
+        // kf2 through kf9 are stored after kf10, and kf11 through kf63 are
+        // stored much later
+        caps.extend(
+            (2..=9).map(|n| (KEY_F2 + usize::from(n - 2), crate::Key::F(n))),
+        );
+        caps.extend(
+            (11..=63)
+                .map(|n| (KEY_F11 + usize::from(n - 11), crate::Key::F(n))),
+        );
+
+        let mut keys: Vec<_> = caps
+            .into_iter()
+            .filter_map(|(idx, key)| {
+                // only escape sequences are handled here, since single
+                // bytes are already handled by the built-in parsing
+                let seq = terminfo.string(idx)?.strip_prefix(b"\x1b")?;
+                (!seq.is_empty()).then(|| (seq.to_vec(), key))
+            })
+            .collect();
+        keys.sort_by_key(|(seq, _)| std::cmp::Reverse(seq.len()));
+        Self { keys }
+    }
+
+    // returns the key for the longest sequence which buf starts with, along
+    // with the length of that sequence (buf should not include the leading
+    // escape)
+    pub(crate) fn find(&self, buf: &[u8]) -> Option<(usize, crate::Key)> {
+        self.keys
+            .iter()
+            .find(|(seq, _)| buf.starts_with(seq))
+            .map(|(seq, key)| (seq.len(), key.clone()))
+    }
+
+    // returns true if buf is a proper prefix of a known sequence
+    pub(crate) fn is_prefix(&self, buf: &[u8]) -> bool {
+        self.keys
+            .iter()
+            .any(|(seq, _)| seq.len() > buf.len() && seq.starts_with(buf))
+    }
+}
+
+fn search_dirs() -> Vec<std::path::PathBuf> {
+    let mut dirs = vec![];
+    if let Some(dir) = std::env::var_os("TERMINFO") {
+        dirs.push(dir.into());
+    }
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(std::path::Path::new(&home).join(".terminfo"));
+    }
+    if let Some(terminfo_dirs) = std::env::var_os("TERMINFO_DIRS") {
+        for dir in std::env::split_paths(&terminfo_dirs) {
+            if dir.as_os_str().is_empty() {
+                dirs.push("/usr/share/terminfo".into());
+            } else {
+                dirs.push(dir);
+            }
+        }
+    }
+    dirs.extend(
+        ["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"]
+            .into_iter()
+            .map(std::path::PathBuf::from),
+    );
+    dirs
+}
+
+fn parse_extended(
+    r: &mut Reader<'_>,
+    number_size: usize,
+) -> Option<std::collections::HashSet<String>> {
+    let booleans_count = r.count()?;
+    let numbers_count = r.count()?;
+    let strings_count = r.count()?;
+    let _items_count = r.count()?;
+    let table_size = r.count()?;
+
+    let booleans = r.take(booleans_count)?.to_vec();
+    r.align();
+    r.skip(numbers_count * number_size)?;
+    let value_offsets = (0..strings_count)
+        .map(|_| r.i16())
+        .collect::<Option<Vec<_>>>()?;
+    let name_offsets = (0..booleans_count + numbers_count + strings_count)
+        .map(|_| r.i16())
+        .collect::<Option<Vec<_>>>()?;
+    let table = r.take(table_size)?;
+
+    // the names are stored after the string values, and their offsets are
+    // relative to the start of the names
+    let names_start = value_offsets
+        .iter()
+        .filter_map(|&offset| {
+            let offset = usize::try_from(offset).ok()?;
+            let len = table.get(offset..)?.iter().position(|&c| c == 0)?;
+            Some(offset + len + 1)
+        })
+        .max()
+        .unwrap_or(0);
+    let names = table.get(names_start..)?;
+
+    Some(
+        booleans
+            .iter()
+            .zip(name_offsets)
+            .filter(|(&value, _)| value == 1)
+            .filter_map(|(_, offset)| {
+                string_at(names, offset)
+                    .and_then(|name| String::from_utf8(name).ok())
+            })
+            .collect(),
+    )
+}
+
+fn string_at(table: &[u8], offset: i16) -> Option<Vec<u8>> {
+    let offset = usize::try_from(offset).ok()?;
+    let s = table.get(offset..)?;
+    let len = s.iter().position(|&c| c == 0)?;
+    Some(s[..len].to_vec())
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, n: usize) -> Option<&[u8]> {
+        let buf = self.data.get(self.pos..self.pos + n)?;
+        self.pos += n;
+        Some(buf)
+    }
+
+    fn skip(&mut self, n: usize) -> Option<()> {
+        self.take(n).map(|_| ())
+    }
+
+    fn align(&mut self) {
+        if self.pos % 2 == 1 {
+            self.pos += 1;
+        }
+    }
+
+    fn i16(&mut self) -> Option<i16> {
+        self.take(2).map(|b| i16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn count(&mut self) -> Option<usize> {
+        usize::try_from(self.i16()?).ok()
+    }
+
+    fn number(&mut self, size: usize) -> Option<i32> {
+        if size == 4 {
+            self.take(4)
+                .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        } else {
+            self.i16().map(i32::from)
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Helpers for laying out text by its display width.
+//!
+//! The width of a string is the number of columns it takes up when written
+//! to the in-memory screen, which is not the same as its length in bytes or
+//! in `char`s: wide characters (such as most CJK characters and emoji) take
+//! up two columns, and combining characters and control characters take up
+//! none. Text is never split in the middle of a grapheme cluster (such as a
+//! letter followed by a combining accent).
+
+use unicode_segmentation::UnicodeSegmentation as _;
+
+/// The ellipsis used by [`align`] and
+/// [`write_truncated`](crate::Draw::write_truncated).
+pub const ELLIPSIS: &str = "\u{2026}";
+
+/// How [`align`] positions text within a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Align {
+    /// Padding is added after the text.
+    #[default]
+    Left,
+    /// Padding is split between both sides of the text, with the extra
+    /// column (if any) going after it.
+    Center,
+    /// Padding is added before the text.
+    Right,
+}
+
+/// Returns the number of columns that `text` takes up on the screen.
+#[must_use]
+pub fn width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// Shortens `text` to at most `width` columns, replacing the end of it with
+/// `ellipsis` if anything had to be removed.
+///
+/// If `ellipsis` itself doesn't fit, the text is cut off without it. The
+/// result can be narrower than `width` if a wide character would have
+/// straddled the limit.
+#[must_use]
+pub fn truncate<'a>(
+    text: &'a str,
+    width: usize,
+    ellipsis: &str,
+) -> std::borrow::Cow<'a, str> {
+    if self::width(text) <= width {
+        return std::borrow::Cow::Borrowed(text);
+    }
+    let ellipsis_width = self::width(ellipsis);
+    if ellipsis_width > width {
+        return std::borrow::Cow::Borrowed(take_width(text, width).0);
+    }
+    let mut truncated =
+        take_width(text, width - ellipsis_width).0.to_string();
+    truncated.push_str(ellipsis);
+    std::borrow::Cow::Owned(truncated)
+}
+
+/// Pads `text` with spaces to exactly `width` columns, positioning it as
+/// specified by `align`. Text which is too wide is first shortened with
+/// [`truncate`], using [`ELLIPSIS`].
+#[must_use]
+pub fn align(text: &str, width: usize, align: Align) -> String {
+    let text = truncate(text, width, ELLIPSIS);
+    let padding = width - self::width(&text);
+    let (before, after) = match align {
+        Align::Left => (0, padding),
+        Align::Center => (padding / 2, padding - padding / 2),
+        Align::Right => (padding, 0),
+    };
+    let mut aligned = String::with_capacity(text.len() + padding);
+    aligned.extend(std::iter::repeat_n(' ', before));
+    aligned.push_str(&text);
+    aligned.extend(std::iter::repeat_n(' ', after));
+    aligned
+}
+
+/// Splits `text` into lines of at most `width` columns, breaking lines at
+/// whitespace where possible.
+///
+/// Newlines in `text` always start a new line, and words which are wider
+/// than `width` on their own are split wherever necessary. Whitespace at the
+/// points where lines were broken is removed.
+#[must_use]
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for word in paragraph.split_whitespace() {
+            let mut word = word;
+            let mut word_width = self::width(word);
+            let space = usize::from(line_width > 0);
+            if line_width + space + word_width <= width {
+                if space > 0 {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                line_width += space + word_width;
+                continue;
+            }
+            if line_width > 0 {
+                lines.push(std::mem::take(&mut line));
+            }
+            while word_width > width {
+                let (start, start_width) = take_width(word, width);
+                if start.is_empty() {
+                    // a single grapheme is wider than the line, so there's
+                    // no way to make it fit
+                    break;
+                }
+                lines.push(start.to_string());
+                word = &word[start.len()..];
+                word_width -= start_width;
+            }
+            line.push_str(word);
+            line_width = word_width;
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+// returns the longest prefix of `text` made of whole grapheme clusters which
+// fits in `width` columns, along with its width
+fn take_width(text: &str, width: usize) -> (&str, usize) {
+    let mut len = 0;
+    let mut total = 0;
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = self::width(grapheme);
+        if total + grapheme_width > width {
+            break;
+        }
+        len += grapheme.len();
+        total += grapheme_width;
+    }
+    (&text[..len], total)
+}
+
+fn char_width(c: char) -> usize {
+    unicode_width::UnicodeWidthChar::width(c).unwrap_or(0)
+}
@@ -0,0 +1,296 @@
+/// A rectangular region of an in-memory screen, returned by
+/// [`view`](crate::Textmode::view).
+///
+/// Positions within a view are relative to its top left corner, and
+/// anything drawn past its edges (or past the edges of the screen) is
+/// clipped rather than wrapping onto the next line. Wide characters which
+/// would straddle the right edge are not drawn at all, and the cell they
+/// would have started in is blanked instead. The helpers in
+/// [`Draw`](crate::Draw) can be used on views as well.
+pub struct View<'a, T: crate::Textmode> {
+    tm: &'a mut T,
+    row: u16,
+    col: u16,
+    rows: u16,
+    cols: u16,
+    cursor: (u16, u16),
+}
+
+impl<'a, T: crate::Textmode> View<'a, T> {
+    /// Creates a view of `tm` with its top left corner at `(row, col)`,
+    /// `rows` lines tall and `cols` columns wide. The view is shrunk to fit
+    /// within the screen if necessary.
+    pub fn new(
+        tm: &'a mut T,
+        row: u16,
+        col: u16,
+        rows: u16,
+        cols: u16,
+    ) -> Self {
+        let (screen_rows, screen_cols) = tm.screen().size();
+        let row = row.min(screen_rows);
+        let col = col.min(screen_cols);
+        Self {
+            tm,
+            row,
+            col,
+            rows: rows.min(screen_rows - row),
+            cols: cols.min(screen_cols - col),
+            cursor: (0, 0),
+        }
+    }
+
+    /// Returns a view of a region within this view, with its top left
+    /// corner at `(row, col)` relative to this view. The new view is shrunk
+    /// to fit within this one if necessary.
+    pub fn view(
+        &mut self,
+        row: u16,
+        col: u16,
+        rows: u16,
+        cols: u16,
+    ) -> View<'_, T> {
+        let row = row.min(self.rows);
+        let col = col.min(self.cols);
+        View {
+            tm: &mut *self.tm,
+            row: self.row + row,
+            col: self.col + col,
+            rows: rows.min(self.rows - row),
+            cols: cols.min(self.cols - col),
+            cursor: (0, 0),
+        }
+    }
+
+    /// Returns the size of the view, as `(rows, cols)`.
+    #[must_use]
+    pub fn size(&self) -> (u16, u16) {
+        (self.rows, self.cols)
+    }
+
+    /// Returns the position of the view's top left corner on the screen, as
+    /// `(row, col)`.
+    #[must_use]
+    pub fn origin(&self) -> (u16, u16) {
+        (self.row, self.col)
+    }
+
+    /// Returns the position of the cursor relative to the view, as
+    /// `(row, col)`. This can be outside of the view, if text was clipped
+    /// or if [`move_to`](Self::move_to) was given a position outside of
+    /// it.
+    #[must_use]
+    pub fn cursor_position(&self) -> (u16, u16) {
+        self.cursor
+    }
+
+    /// Moves the cursor, relative to the view. The screen's cursor is moved
+    /// to the nearest position within the view.
+    pub fn move_to(&mut self, row: u16, col: u16) {
+        self.cursor = (row, col);
+        self.sync_cursor();
+    }
+
+    /// Writes a string of printable characters at the cursor position,
+    /// clipping anything that doesn't fit within the view. Control
+    /// characters are ignored.
+    pub fn write_str(&mut self, text: &str) {
+        let (row, col) = self.cursor;
+        let mut visible = String::new();
+        let mut width: u16 = 0;
+        let mut clipped = false;
+        let mut blank = false;
+        for c in text.chars() {
+            // control characters have no width
+            let Some(c_width) = unicode_width::UnicodeWidthChar::width(c)
+            else {
+                continue;
+            };
+            let c_width = u16::try_from(c_width).unwrap_or(u16::MAX);
+            if !clipped {
+                let start = col.saturating_add(width);
+                if start.saturating_add(c_width) > self.cols {
+                    clipped = true;
+                    // a wide character straddling the edge still covers
+                    // the cells before the edge
+                    blank = start < self.cols;
+                } else {
+                    visible.push(c);
+                }
+            }
+            width = width.saturating_add(c_width);
+        }
+
+        if row < self.rows && col < self.cols {
+            self.sync_cursor();
+            self.tm.write_str(&visible);
+            if blank {
+                self.tm.write_str(" ");
+            }
+        }
+
+        // the cursor moves past clipped text too, so that later writes on
+        // the same line stay clipped
+        self.cursor.1 = col.saturating_add(width);
+        self.sync_cursor();
+    }
+
+    /// Clears the view, without moving the cursor.
+    pub fn clear(&mut self) {
+        for row in 0..self.rows {
+            self.erase(row, 0);
+        }
+        self.sync_cursor();
+    }
+
+    /// Clears the rest of the line containing the cursor, from the cursor
+    /// to the right edge of the view.
+    pub fn clear_line(&mut self) {
+        let (row, col) = self.cursor;
+        self.erase(row, col);
+        self.sync_cursor();
+    }
+
+    /// Returns the drawing attributes which are currently active.
+    #[must_use]
+    pub fn current_style(&self) -> crate::Style {
+        self.tm.current_style()
+    }
+
+    /// Sets all of the drawing attributes for subsequent drawing operations
+    /// at once.
+    pub fn set_style(&mut self, style: crate::Style) {
+        self.tm.set_style(style);
+    }
+
+    /// Clears the screen's currently active drawing attributes.
+    pub fn reset_attributes(&mut self) {
+        self.tm.reset_attributes();
+    }
+
+    /// Sets the foreground color for subsequent drawing operations.
+    pub fn set_fgcolor(&mut self, color: vt100::Color) {
+        self.tm.set_fgcolor(color);
+    }
+
+    /// Sets the background color for subsequent drawing operations.
+    pub fn set_bgcolor(&mut self, color: vt100::Color) {
+        self.tm.set_bgcolor(color);
+    }
+
+    /// Sets whether subsequent text should be bold.
+    pub fn set_bold(&mut self, bold: bool) {
+        self.tm.set_bold(bold);
+    }
+
+    /// Sets whether subsequent text should be italic.
+    pub fn set_italic(&mut self, italic: bool) {
+        self.tm.set_italic(italic);
+    }
+
+    /// Sets whether subsequent text should be underlined.
+    pub fn set_underline(&mut self, underline: bool) {
+        self.tm.set_underline(underline);
+    }
+
+    /// Sets whether subsequent text should have its colors inverted.
+    pub fn set_inverse(&mut self, inverse: bool) {
+        self.tm.set_inverse(inverse);
+    }
+
+    /// Sets whether subsequent text should be drawn dimmer than usual.
+    pub fn set_dim(&mut self, dim: bool) {
+        self.tm.set_dim(dim);
+    }
+
+    /// Sets whether subsequent text should be underlined with the given
+    /// kind of underline.
+    pub fn set_underline_style(&mut self, style: crate::UnderlineStyle) {
+        self.tm.set_underline_style(style);
+    }
+
+    /// Sets the color of the underline for subsequent text.
+    pub fn set_underline_color(&mut self, color: vt100::Color) {
+        self.tm.set_underline_color(color);
+    }
+
+    /// Sets whether subsequent text should blink.
+    pub fn set_blink(&mut self, blink: bool) {
+        self.tm.set_blink(blink);
+    }
+
+    /// Sets whether subsequent text should be hidden.
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.tm.set_hidden(hidden);
+    }
+
+    /// Sets whether subsequent text should be struck through.
+    pub fn set_strikethrough(&mut self, strikethrough: bool) {
+        self.tm.set_strikethrough(strikethrough);
+    }
+
+    /// Sets whether subsequent text should have a line drawn over it.
+    pub fn set_overline(&mut self, overline: bool) {
+        self.tm.set_overline(overline);
+    }
+
+    fn clip(&self) -> crate::draw::Clip {
+        crate::draw::Clip {
+            row: self.row,
+            col: self.col,
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
+    // erases from (row, col) to the right edge of the view
+    fn erase(&mut self, row: u16, col: u16) {
+        if row >= self.rows || col >= self.cols {
+            return;
+        }
+        self.tm.move_to(self.row + row, self.col + col);
+        self.tm.write(b"\x1b[");
+        crate::private::Output::write_u16(self.tm, self.cols - col);
+        self.tm.write(b"X");
+    }
+
+    fn sync_cursor(&mut self) {
+        if self.rows == 0 || self.cols == 0 {
+            return;
+        }
+        let row = self.cursor.0.min(self.rows - 1);
+        let col = self.cursor.1.min(self.cols - 1);
+        self.tm.move_to(self.row + row, self.col + col);
+    }
+}
+
+impl<T: crate::Textmode> crate::private::Draw for View<'_, T> {
+    type Target = T;
+
+    fn target(&mut self) -> (&mut T, crate::draw::Clip) {
+        let clip = self.clip();
+        (self.tm, clip)
+    }
+
+    fn cursor(&self) -> (u16, u16) {
+        self.cursor
+    }
+
+    fn goto(&mut self, row: u16, col: u16) {
+        self.move_to(row, col);
+    }
+
+    fn put_str(&mut self, text: &str) {
+        self.write_str(text);
+    }
+
+    fn style(&self) -> crate::Style {
+        self.current_style()
+    }
+
+    fn put_style(&mut self, style: crate::Style) {
+        self.set_style(style);
+    }
+}
+
+impl<T: crate::Textmode> crate::Draw for View<'_, T> {}
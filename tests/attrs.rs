@@ -0,0 +1,172 @@
+use textmode::Textmode as _;
+
+mod common;
+
+// another output is used as the terminal, since it understands all of the
+// attributes that textmode can generate
+fn terminal_contents(bytes: &[u8]) -> textmode::Snapshot {
+    let mut term = common::output(4, 20);
+    term.write(bytes);
+    term.snapshot()
+}
+
+#[test]
+fn test_setters() {
+    let mut tm = common::output(4, 20);
+    tm.set_dim(true);
+    tm.set_strikethrough(true);
+    tm.set_fgcolor(textmode::color::RED);
+    assert_eq!(
+        tm.current_style(),
+        textmode::Style::new()
+            .dim(true)
+            .strikethrough(true)
+            .fgcolor(textmode::color::RED)
+    );
+    tm.write_str("a");
+
+    tm.set_strikethrough(false);
+    tm.set_blink(true);
+    tm.set_hidden(true);
+    tm.set_overline(true);
+    tm.set_underline_style(textmode::UnderlineStyle::Curly);
+    tm.set_underline_color(textmode::Color::Rgb(1, 2, 3));
+    assert_eq!(
+        tm.current_style(),
+        textmode::Style::new()
+            .dim(true)
+            .blink(true)
+            .hidden(true)
+            .overline(true)
+            .underline_style(textmode::UnderlineStyle::Curly)
+            .underline_color(textmode::Color::Rgb(1, 2, 3))
+            .fgcolor(textmode::color::RED)
+    );
+    tm.write_str("b");
+
+    tm.reset_attributes();
+    assert_eq!(tm.current_style(), textmode::Style::new());
+    tm.write_str("c");
+
+    let snapshot = tm.snapshot();
+    let cells = &snapshot.cells[0];
+    assert!(cells[0].attrs.dim && cells[0].attrs.strikethrough);
+    assert!(!cells[1].attrs.strikethrough && cells[1].attrs.overline);
+    assert_eq!(
+        cells[1].attrs.underline_style,
+        textmode::UnderlineStyle::Curly
+    );
+    assert_eq!(cells[2].attrs, textmode::Style::new());
+    assert_eq!(
+        tm.screen().cell(0, 0).unwrap().fgcolor(),
+        textmode::color::RED
+    );
+}
+
+#[test]
+fn test_bold_and_dim() {
+    let mut tm = common::output(4, 20);
+    tm.set_bold(true);
+    tm.set_dim(true);
+    tm.set_bold(false);
+    assert_eq!(tm.current_style(), textmode::Style::new().dim(true));
+    tm.set_bold(true);
+    tm.set_dim(false);
+    assert_eq!(tm.current_style(), textmode::Style::new().bold(true));
+    tm.write(b"\x1b[2;22m");
+    assert_eq!(tm.current_style(), textmode::Style::new());
+}
+
+#[test]
+fn test_raw_sgr() {
+    let mut tm = common::output(4, 20);
+    tm.write(b"\x1b[1;4:3;58:5:9;31");
+    tm.write(b"mx\x1b[4:0;59my");
+    let snapshot = tm.snapshot();
+    let x = snapshot.cells[0][0].attrs;
+    assert!(x.bold && x.underline);
+    assert_eq!(x.underline_style, textmode::UnderlineStyle::Curly);
+    assert_eq!(x.underline_color, textmode::Color::Idx(9));
+    assert_eq!(x.fgcolor, textmode::color::RED);
+    let y = snapshot.cells[0][1].attrs;
+    assert_eq!(y, textmode::Style::new().bold(true).fgcolor(x.fgcolor));
+
+    // the parameters that vt100 doesn't understand don't confuse it about
+    // the ones it does
+    tm.write(b"\x1b[0;9;38;5;100;53m");
+    assert_eq!(
+        tm.current_style(),
+        textmode::Style::new()
+            .strikethrough(true)
+            .overline(true)
+            .fgcolor(textmode::Color::Idx(100))
+    );
+}
+
+#[test]
+fn test_refresh() {
+    let buf = common::SharedBuf::default();
+    let mut tm = textmode::blocking::Output::from_writer(buf.clone());
+    tm.color_depth(textmode::ColorDepth::TrueColor);
+    tm.set_size(4, 20);
+    let mut written = vec![];
+
+    tm.write_str("plain");
+    tm.set_dim(true);
+    tm.write_str("dim");
+    tm.set_underline_style(textmode::UnderlineStyle::Double);
+    tm.set_underline_color(textmode::color::BLUE);
+    tm.move_to(2, 3);
+    tm.write_str("under");
+    tm.refresh().unwrap();
+    written.extend(buf.take());
+    assert_eq!(terminal_contents(&written), tm.snapshot());
+
+    // changing only the extra attributes of existing text
+    tm.reset_attributes();
+    tm.move_to(0, 5);
+    tm.set_strikethrough(true);
+    tm.write_str("dim");
+    tm.move_to(2, 3);
+    tm.set_underline(false);
+    tm.write_str("under");
+    tm.refresh().unwrap();
+    written.extend(buf.take());
+    assert_eq!(terminal_contents(&written), tm.snapshot());
+
+    tm.hard_refresh().unwrap();
+    assert_eq!(terminal_contents(&buf.take()), tm.snapshot());
+}
+
+#[test]
+fn test_snapshot() {
+    let mut tm = common::output(4, 20);
+    tm.set_overline(true);
+    tm.set_underline_style(textmode::UnderlineStyle::Dotted);
+    tm.write_str("foo");
+    tm.set_hidden(true);
+    let snapshot = tm.snapshot();
+
+    let mut restored = common::output(4, 20);
+    restored.restore_snapshot(&snapshot);
+    assert_eq!(restored.snapshot(), snapshot);
+}
+
+#[test]
+fn test_start_late() {
+    // the extra attributes are only kept track of once they are first used,
+    // and they need to line up with the text that was already there
+    let mut tm = common::output(4, 20);
+    tm.set_bold(true);
+    tm.move_to(1, 17);
+    tm.write_str("wrapped");
+    tm.set_strikethrough(true);
+    tm.write_str("x\r\ny");
+    let snapshot = tm.snapshot();
+    assert!(snapshot.cells[1][17].attrs.bold);
+    assert!(!snapshot.cells[2][3].attrs.strikethrough);
+    assert!(snapshot.cells[2][4].attrs.strikethrough);
+    assert!(snapshot.cells[2][4].attrs.bold);
+    assert!(snapshot.cells[3][0].attrs.strikethrough);
+    assert_eq!(snapshot.cells[3][0].contents, "y");
+}
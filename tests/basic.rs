@@ -42,3 +42,132 @@ fn test_async() {
         pty.write_all(b"a").unwrap();
     });
 }
+
+#[test]
+fn test_auto_resize() {
+    let fixture = fixtures::Fixture::new("basic");
+    fixture.build().run(&["--auto-resize"], |pty| {
+        pty.write_all(b"a").unwrap();
+        assert_eq!(fixtures::read(pty), b"\x1b[6;6Hfoo");
+
+        pty.write_all(b"a").unwrap();
+        assert!(!fixtures::read_ready(&pty));
+
+        pty.resize(pty_process::Size::new(30, 100)).unwrap();
+        pty.write_all(b"a").unwrap();
+        assert_eq!(
+            fixtures::read(pty),
+            b"\x1b[?25h\x1b[m\x1b[H\x1b[J\x1b[6;6Hfoo\x1b[9;9H\x1b[32mbar\
+              \x1b[12;12H\x1b[mbaz\x1b>\x1b[?1l\x1b[?2004l"
+        );
+
+        pty.write_all(b"a").unwrap();
+    });
+}
+
+#[test]
+fn test_auto_resize_async() {
+    let mut fixture = fixtures::Fixture::new("basic");
+    fixture.features("async");
+    fixture.build().run(&["--auto-resize"], |pty| {
+        pty.write_all(b"a").unwrap();
+        assert_eq!(fixtures::read(pty), b"\x1b[6;6Hfoo");
+
+        pty.write_all(b"a").unwrap();
+        assert!(!fixtures::read_ready(&pty));
+
+        pty.resize(pty_process::Size::new(30, 100)).unwrap();
+        pty.write_all(b"a").unwrap();
+        assert_eq!(
+            fixtures::read(pty),
+            b"\x1b[?25h\x1b[m\x1b[H\x1b[J\x1b[6;6Hfoo\x1b[9;9H\x1b[32mbar\
+              \x1b[12;12H\x1b[mbaz\x1b>\x1b[?1l\x1b[?2004l"
+        );
+
+        pty.write_all(b"a").unwrap();
+    });
+}
+
+#[test]
+fn test_color_depth() {
+    let fixture = fixtures::Fixture::new("basic");
+    run_color_depth_test(&mut fixture.build());
+}
+
+#[test]
+fn test_color_depth_async() {
+    let mut fixture = fixtures::Fixture::new("basic");
+    fixture.features("async");
+    run_color_depth_test(&mut fixture.build());
+}
+
+fn run_color_depth_test(fixture: &mut fixtures::BuiltFixture) {
+    for (args, color) in [
+        (
+            &["--rgb", "--color-depth=truecolor"][..],
+            &b"\x1b[38;2;0;192;0m"[..],
+        ),
+        (&["--rgb", "--color-depth=256"][..], &b"\x1b[38;5;34m"[..]),
+        (&["--rgb", "--color-depth=16"][..], &b"\x1b[32m"[..]),
+        (&["--color-depth=16"][..], &b"\x1b[32m"[..]),
+        (&["--rgb", "--color-depth=mono"][..], &b""[..]),
+        (&["--color-depth=mono"][..], &b""[..]),
+    ] {
+        fixture.run(args, |pty| {
+            pty.write_all(b"a").unwrap();
+            assert_eq!(fixtures::read(pty), b"\x1b[6;6Hfoo");
+
+            pty.write_all(b"a").unwrap();
+            assert!(!fixtures::read_ready(&pty));
+
+            pty.write_all(b"a").unwrap();
+            let mut expected = b"\x1b[9;9H".to_vec();
+            expected.extend(color);
+            expected.extend(b"bar\x1b[12;12H\x1b[mbaz");
+            assert_eq!(fixtures::read(pty), expected, "{args:?}");
+
+            pty.write_all(b"a").unwrap();
+        });
+    }
+}
+
+#[test]
+fn test_tty() {
+    let fixture = fixtures::Fixture::new("basic");
+    fixture.build().run(&["--tty"], |pty| {
+        pty.write_all(b"a").unwrap();
+        assert_eq!(fixtures::read(pty), b"\x1b[6;6Hfoo");
+
+        pty.write_all(b"a").unwrap();
+        assert!(!fixtures::read_ready(&pty));
+
+        pty.write_all(b"a").unwrap();
+        assert_eq!(
+            fixtures::read(pty),
+            b"\x1b[9;9H\x1b[32mbar\x1b[12;12H\x1b[mbaz"
+        );
+
+        pty.write_all(b"a").unwrap();
+    });
+}
+
+#[test]
+fn test_tty_async() {
+    let mut fixture = fixtures::Fixture::new("basic");
+    fixture.features("async");
+    fixture.build().run(&["--tty"], |pty| {
+        pty.write_all(b"a").unwrap();
+        assert_eq!(fixtures::read(pty), b"\x1b[6;6Hfoo");
+
+        pty.write_all(b"a").unwrap();
+        assert!(!fixtures::read_ready(&pty));
+
+        pty.write_all(b"a").unwrap();
+        assert_eq!(
+            fixtures::read(pty),
+            b"\x1b[9;9H\x1b[32mbar\x1b[12;12H\x1b[mbaz"
+        );
+
+        pty.write_all(b"a").unwrap();
+    });
+}
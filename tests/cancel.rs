@@ -0,0 +1,131 @@
+#![cfg(feature = "async")]
+
+use futures::FutureExt as _;
+use tokio::io::AsyncWriteExt as _;
+
+// writes the input a byte at a time, polling read_key once after each byte
+// and dropping the future if it isn't ready yet. this cancels read_key at
+// whichever await point it was waiting at for each partial key.
+async fn read_keys_cancelling(
+    mut input: textmode::Input,
+    mut writer: tokio::io::DuplexStream,
+    bytes: &[u8],
+) -> Vec<textmode::Key> {
+    let mut keys = vec![];
+    for byte in bytes {
+        writer.write_all(std::slice::from_ref(byte)).await.unwrap();
+        while let Some(key) = input.read_key().now_or_never() {
+            keys.push(key.unwrap().unwrap());
+        }
+    }
+    drop(writer);
+    while let Some(key) = input.read_key().await.unwrap() {
+        keys.push(key);
+    }
+    keys
+}
+
+#[tokio::test]
+async fn test_cancel_utf8() {
+    let (reader, writer) = tokio::io::duplex(64);
+    let input = textmode::Input::from_reader(reader);
+    assert_eq!(
+        read_keys_cancelling(input, writer, "a\u{3042}b".as_bytes()).await,
+        vec![
+            textmode::Key::Char('a'),
+            textmode::Key::Char('\u{3042}'),
+            textmode::Key::Char('b'),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_cancel_escape_timeout() {
+    let (reader, writer) = tokio::io::duplex(64);
+    let mut input = textmode::Input::from_reader(reader);
+    input.escape_timeout(std::time::Duration::from_secs(60));
+    assert_eq!(
+        read_keys_cancelling(input, writer, b"\x1b[1;5Ax\x1bOP").await,
+        vec![
+            textmode::Key::Modified(
+                Box::new(textmode::Key::Up),
+                textmode::Modifiers::CTRL,
+            ),
+            textmode::Key::Char('x'),
+            textmode::Key::F(1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_cancel_paste() {
+    let (reader, writer) = tokio::io::duplex(64);
+    let mut input = textmode::Input::from_reader(reader);
+    // so that the start of the paste isn't parsed as a lone escape
+    input.escape_timeout(std::time::Duration::from_secs(60));
+    assert_eq!(
+        read_keys_cancelling(input, writer, b"\x1b[200~hi\x1b[201~!").await,
+        vec![
+            textmode::Key::Paste("hi".to_string()),
+            textmode::Key::Char('!'),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_cancel_strings() {
+    let (reader, writer) = tokio::io::duplex(64);
+    let mut input = textmode::Input::from_reader(reader);
+    input.parse_single(false);
+    let keys = read_keys_cancelling(input, writer, b"ab\xe3\x81\x82").await;
+    let bytes: Vec<u8> = keys
+        .into_iter()
+        .flat_map(textmode::Key::into_bytes)
+        .collect();
+    assert_eq!(bytes, b"ab\xe3\x81\x82");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_cancel_keeps_escape_deadline() {
+    let (reader, mut writer) = tokio::io::duplex(64);
+    let mut input = textmode::Input::from_reader(reader);
+    input.escape_timeout(std::time::Duration::from_millis(500));
+
+    writer.write_all(b"\x1b").await.unwrap();
+    assert!(input.read_key().now_or_never().is_none());
+    tokio::time::advance(std::time::Duration::from_millis(300)).await;
+    assert!(input.read_key().now_or_never().is_none());
+    tokio::time::advance(std::time::Duration::from_millis(300)).await;
+
+    // the timeout started when the escape was first read, so it has
+    // already expired
+    assert_eq!(
+        input.read_key().now_or_never().unwrap().unwrap(),
+        Some(textmode::Key::Escape)
+    );
+}
+
+#[tokio::test]
+async fn test_cancel_read_binding() {
+    let (reader, mut writer) = tokio::io::duplex(64);
+    let mut input = textmode::Input::from_reader(reader);
+    let mut keymap = textmode::Keymap::new();
+    keymap.bind([textmode::Key::Char('g'), textmode::Key::Char('g')], "top");
+    keymap.bind([textmode::Key::Char('x')], "x");
+    keymap.timeout(Some(std::time::Duration::from_secs(60)));
+
+    writer.write_all(b"g").await.unwrap();
+    for _ in 0..3 {
+        assert!(input.read_binding(&mut keymap).now_or_never().is_none());
+    }
+    assert!(keymap.is_pending());
+    writer.write_all(b"gx").await.unwrap();
+    assert_eq!(
+        input.read_binding(&mut keymap).await.unwrap(),
+        Some(textmode::KeymapEvent::Action("top"))
+    );
+    assert_eq!(
+        input.read_binding(&mut keymap).await.unwrap(),
+        Some(textmode::KeymapEvent::Action("x"))
+    );
+}
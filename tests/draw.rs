@@ -0,0 +1,104 @@
+use textmode::Draw as _;
+use textmode::Textmode as _;
+
+mod common;
+
+#[test]
+fn test_box_styles() {
+    let mut tm = common::output(6, 12);
+    tm.draw_box(0, 0, 3, 4, textmode::BorderStyle::Single);
+    tm.draw_box(0, 4, 3, 4, textmode::BorderStyle::Double);
+    tm.draw_box(0, 8, 3, 4, textmode::BorderStyle::Rounded);
+    tm.draw_box(3, 0, 3, 4, textmode::BorderStyle::Heavy);
+    tm.draw_box(3, 4, 3, 4, textmode::BorderStyle::Ascii);
+    assert_eq!(
+        common::rows(tm.screen()),
+        [
+            "┌──┐╔══╗╭──╮",
+            "│  │║  ║│  │",
+            "└──┘╚══╝╰──╯",
+            "┏━━┓+--+",
+            "┃  ┃|  |",
+            "┗━━┛+--+",
+        ]
+    );
+    // drawing doesn't move the cursor
+    assert_eq!(tm.screen().cursor_position(), (0, 0));
+}
+
+#[test]
+fn test_junctions() {
+    let mut tm = common::output(6, 12);
+    tm.move_to(5, 11);
+    tm.set_fgcolor(textmode::color::GREEN);
+    tm.draw_box(0, 0, 5, 9, textmode::BorderStyle::Single);
+    tm.draw_hline(2, 0, 9, textmode::BorderStyle::Single);
+    tm.draw_vline(0, 4, 5, textmode::BorderStyle::Single);
+    tm.draw_vline(2, 10, 1, textmode::BorderStyle::Heavy);
+    tm.draw_hline(4, 2, 9, textmode::BorderStyle::Single);
+    assert_eq!(
+        common::rows(tm.screen())[..5],
+        [
+            "┌───┬───┐",
+            "│   │   │",
+            "├───┼───┤ ┃",
+            "│   │   │",
+            "└───┴───┴──",
+        ]
+    );
+    assert_eq!(
+        tm.screen().cell(2, 4).unwrap().fgcolor(),
+        textmode::color::GREEN
+    );
+    assert_eq!(tm.screen().cursor_position(), (5, 11));
+
+    // the ends of lines are joined up as ends, whichever order the lines
+    // are drawn in
+    let expected = common::rows(tm.screen());
+    let mut tm = common::output(6, 12);
+    tm.draw_hline(4, 2, 9, textmode::BorderStyle::Single);
+    tm.draw_vline(2, 10, 1, textmode::BorderStyle::Heavy);
+    tm.draw_vline(0, 4, 5, textmode::BorderStyle::Single);
+    tm.draw_hline(2, 0, 9, textmode::BorderStyle::Single);
+    tm.draw_box(0, 0, 5, 9, textmode::BorderStyle::Single);
+    assert_eq!(common::rows(tm.screen()), expected);
+
+    // cells which were overwritten since are joined up by their contents
+    tm.move_to(2, 8);
+    tm.write_str("─");
+    tm.draw_vline(2, 8, 2, textmode::BorderStyle::Single);
+    assert_eq!(common::rows(tm.screen())[2], "├───┼───┬ ┃");
+}
+
+#[test]
+fn test_fill_rect() {
+    let mut tm = common::output(6, 12);
+    tm.set_bgcolor(textmode::color::BLUE);
+    tm.fill_rect(1, 9, 2, 5, '#');
+    tm.fill_rect(4, 2, 0, 5, '#');
+    assert_eq!(
+        common::rows(tm.screen())[..4],
+        ["", "         ###", "         ###", ""]
+    );
+    assert_eq!(
+        tm.screen().cell(2, 11).unwrap().bgcolor(),
+        textmode::color::BLUE
+    );
+    // the screen didn't scroll or wrap
+    assert_eq!(common::rows(tm.screen())[3], "");
+}
+
+#[test]
+fn test_view_box() {
+    let mut tm = common::output(6, 12);
+    let mut view = tm.view(1, 1, 4, 6);
+    view.draw_box(0, 0, 4, 6, textmode::BorderStyle::Rounded);
+    view.draw_hline(1, 0, 6, textmode::BorderStyle::Rounded);
+    view.fill_rect(2, 1, 1, 10, '.');
+    // only the part of the box inside the view is drawn
+    view.draw_box(3, 3, 4, 4, textmode::BorderStyle::Single);
+    assert_eq!(
+        common::rows(tm.screen()),
+        ["", " ╭────╮", " ├────┤", " │.....", " ╰──┬─┴", "",]
+    );
+}
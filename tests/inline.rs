@@ -0,0 +1,171 @@
+use textmode::Textmode as _;
+
+mod common;
+
+// a terminal with eight lines of shell output, and the cursor on the line
+// below them
+fn terminal() -> vt100::Parser {
+    let mut term = vt100::Parser::new(10, 20, 0);
+    for i in 0..8 {
+        term.process(format!("line {i}\r\n").as_bytes());
+    }
+    term
+}
+
+fn draw(tm: &mut impl textmode::Textmode) {
+    tm.move_to(0, 0);
+    tm.write_str("foo");
+    tm.move_to(3, 5);
+    tm.set_fgcolor(textmode::color::RED);
+    tm.write_str("bar");
+    tm.reset_attributes();
+    tm.move_to(1, 2);
+}
+
+fn redraw(tm: &mut impl textmode::Textmode) {
+    tm.move_to(0, 0);
+    tm.write_str("baz");
+    tm.move_to(2, 0);
+    tm.write_str("quux");
+}
+
+#[test]
+fn test_inline() {
+    let buf = common::SharedBuf::default();
+    let mut term = terminal();
+    let mut tm = textmode::blocking::Output::from_writer(buf.clone());
+    tm.set_size(10, 20);
+    tm.inline(4, textmode::InlineCleanup::Keep).unwrap();
+    assert_eq!(tm.screen().size(), (4, 20));
+    term.process(&buf.take());
+    // the region started on the line the cursor was on, and the terminal
+    // scrolled to make room for it
+    assert_eq!(term.screen().cursor_position(), (6, 0));
+    assert_eq!(
+        common::rows(term.screen())[..6],
+        ["line 2", "line 3", "line 4", "line 5", "line 6", "line 7"]
+    );
+    assert_eq!(common::rows(term.screen())[6..], ["", "", "", ""]);
+
+    draw(&mut tm);
+    tm.refresh().unwrap();
+    term.process(&buf.take());
+    assert_eq!(
+        common::rows(term.screen())[5..],
+        ["line 7", "foo", "", "", "     bar"]
+    );
+    assert_eq!(
+        term.screen().cell(9, 5).unwrap().fgcolor(),
+        textmode::color::RED
+    );
+    assert_eq!(term.screen().cursor_position(), (7, 2));
+
+    redraw(&mut tm);
+    tm.refresh().unwrap();
+    term.process(&buf.take());
+    assert_eq!(
+        common::rows(term.screen())[5..],
+        ["line 7", "baz", "", "quux", "     bar"]
+    );
+
+    tm.hard_refresh().unwrap();
+    term.process(&buf.take());
+    assert_eq!(
+        common::rows(term.screen())[5..],
+        ["line 7", "baz", "", "quux", "     bar"]
+    );
+
+    // the frame stays in place, and the cursor ends up below it
+    drop(tm);
+    term.process(&buf.take());
+    assert_eq!(
+        common::rows(term.screen())[4..],
+        ["line 7", "baz", "", "quux", "     bar", ""]
+    );
+    assert_eq!(term.screen().cursor_position(), (9, 0));
+}
+
+#[test]
+fn test_inline_erase() {
+    let buf = common::SharedBuf::default();
+    let mut term = vt100::Parser::new(10, 20, 0);
+    term.process(b"$ ");
+    let mut tm = textmode::blocking::Output::from_writer(buf.clone());
+    tm.set_size(10, 20);
+    // the region can't be taller than the terminal
+    tm.inline(20, textmode::InlineCleanup::Erase).unwrap();
+    assert_eq!(tm.screen().size(), (10, 20));
+    draw(&mut tm);
+    tm.refresh().unwrap();
+    term.process(&buf.take());
+    assert_eq!(
+        common::rows(term.screen())[..4],
+        ["foo", "", "", "     bar"]
+    );
+
+    tm.cleanup().unwrap();
+    term.process(&buf.take());
+    assert!(common::rows(term.screen()).iter().all(String::is_empty));
+    assert_eq!(term.screen().cursor_position(), (0, 0));
+
+    // cleaning up only happens once
+    drop(tm);
+    assert!(buf.take().is_empty());
+}
+
+#[test]
+fn test_inline_after_refresh() {
+    let buf = common::SharedBuf::default();
+    let mut term = terminal();
+    let mut tm = textmode::blocking::Output::from_writer(buf.clone());
+    tm.set_size(10, 20);
+    tm.move_to(8, 3);
+    tm.write_str("$ ");
+    tm.refresh().unwrap();
+    term.process(&buf.take());
+
+    // the region starts on the line the cursor was left on, even though the
+    // in-memory screen's cursor was somewhere else
+    tm.inline(2, textmode::InlineCleanup::Keep).unwrap();
+    tm.move_to(0, 0);
+    tm.write_str("foo");
+    tm.move_to(1, 1);
+    tm.write_str("bar");
+    tm.refresh().unwrap();
+    term.process(&buf.take());
+    assert_eq!(common::rows(term.screen())[7..], ["line 7", "foo", " bar"]);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_inline_async() {
+    let buf = common::SharedBuf::default();
+    let mut term = terminal();
+    let mut tm = textmode::Output::from_writer(buf.clone());
+    tm.set_size(10, 20);
+    tm.inline(4, textmode::InlineCleanup::Keep).await.unwrap();
+    draw(&mut tm);
+    tm.refresh().await.unwrap();
+    redraw(&mut tm);
+    tm.refresh().await.unwrap();
+    tm.cleanup().await.unwrap();
+    term.process(&buf.take());
+    assert_eq!(
+        common::rows(term.screen())[4..],
+        ["line 7", "baz", "", "quux", "     bar", ""]
+    );
+    assert_eq!(term.screen().cursor_position(), (9, 0));
+
+    let mut tm = textmode::Output::from_writer(buf.clone());
+    tm.set_size(10, 20);
+    tm.inline(4, textmode::InlineCleanup::Erase).await.unwrap();
+    draw(&mut tm);
+    tm.refresh().await.unwrap();
+    tm.cleanup().await.unwrap();
+    term.process(&buf.take());
+    assert_eq!(
+        common::rows(term.screen())[1..],
+        ["line 7", "baz", "", "quux", "     bar", "", "", "", ""]
+    );
+    assert_eq!(term.screen().cursor_position(), (6, 0));
+}
@@ -57,6 +57,556 @@ fn test_async() {
     }
 }
 
+#[test]
+fn test_kitty_keyboard() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    let mut run = fixture.build();
+    run_kitty_keyboard_test(&mut run);
+}
+
+#[test]
+fn test_kitty_keyboard_async() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    fixture.features("async");
+    let mut run = fixture.build();
+    run_kitty_keyboard_test(&mut run);
+}
+
+fn run_kitty_keyboard_test(fixture: &mut fixtures::BuiltFixture) {
+    fixture.run(&["--enable-kitty-keyboard"], |pty| {
+        assert_eq!(fixtures::read(pty), b"\x1b[>3u");
+
+        let mut r = std::io::BufReader::new(pty);
+        r.get_mut().write_all(b"\x1b[27u").unwrap();
+        assert_line(
+            &mut r,
+            "Event(KeyEvent { key: Escape, modifiers: Modifiers(NONE), \
+             kind: Press }): [27, 91, 50, 55, 59, 49, 58, 49, 117]",
+        );
+        r.get_mut().write_all(b"\x1b[97;3:2u").unwrap();
+        assert_line(
+            &mut r,
+            "Event(KeyEvent { key: Char('a'), modifiers: Modifiers(ALT), \
+             kind: Repeat }): [27, 91, 57, 55, 59, 51, 58, 50, 117]",
+        );
+        r.get_mut().write_all(b"\x1b[9u").unwrap();
+        assert_line(
+            &mut r,
+            "Event(KeyEvent { key: Ctrl(105), modifiers: Modifiers(NONE), \
+             kind: Press }): [27, 91, 57, 59, 49, 58, 49, 117]",
+        );
+        r.get_mut().write_all(b"\x1b[57404u").unwrap();
+        assert_line(
+            &mut r,
+            "Event(KeyEvent { key: Char('5'), modifiers: Modifiers(NONE), \
+             kind: Press }): [27, 91, 53, 51, 59, 49, 58, 49, 117]",
+        );
+        r.get_mut().write_all(b"\x1b[57388u").unwrap();
+        assert_line(
+            &mut r,
+            "Event(KeyEvent { key: F(25), modifiers: Modifiers(NONE), \
+             kind: Press }): \
+             [27, 91, 53, 55, 51, 56, 56, 59, 49, 58, 49, 117]",
+        );
+        // the left shift key, which has no equivalent key
+        r.get_mut().write_all(b"\x1b[57441;2u").unwrap();
+        assert_line(
+            &mut r,
+            "Bytes([27, 91, 53, 55, 52, 52, 49, 59, 50, 117]): \
+             [27, 91, 53, 55, 52, 52, 49, 59, 50, 117]",
+        );
+        assert_no_more_lines(&mut r);
+
+        write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+        assert_line(&mut r, "Ctrl(99): [3]");
+        assert_cleanup(&mut r, b"\x1b[<u");
+    });
+}
+
+#[test]
+fn test_mouse() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    let mut run = fixture.build();
+    run_mouse_test(&mut run);
+}
+
+#[test]
+fn test_mouse_async() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    fixture.features("async");
+    let mut run = fixture.build();
+    run_mouse_test(&mut run);
+}
+
+fn run_mouse_test(fixture: &mut fixtures::BuiltFixture) {
+    fixture.run(&["--enable-mouse"], |pty| {
+        assert_eq!(fixtures::read(pty), b"\x1b[?1002h\x1b[?1006h");
+
+        let mut r = std::io::BufReader::new(pty);
+        r.get_mut().write_all(b"\x1b[<0;5;3M").unwrap();
+        assert_line(
+            &mut r,
+            "Mouse(MouseEvent { kind: Press, button: Some(Left), \
+             modifiers: Modifiers(NONE), row: 2, col: 4 }): \
+             [27, 91, 60, 48, 59, 53, 59, 51, 77]",
+        );
+        r.get_mut().write_all(b"\x1b[<34;6;3M").unwrap();
+        assert_line(
+            &mut r,
+            "Mouse(MouseEvent { kind: Drag, button: Some(Right), \
+             modifiers: Modifiers(NONE), row: 2, col: 5 }): \
+             [27, 91, 60, 51, 52, 59, 54, 59, 51, 77]",
+        );
+        r.get_mut().write_all(b"\x1b[<2;6;3m").unwrap();
+        assert_line(
+            &mut r,
+            "Mouse(MouseEvent { kind: Release, button: Some(Right), \
+             modifiers: Modifiers(NONE), row: 2, col: 5 }): \
+             [27, 91, 60, 50, 59, 54, 59, 51, 109]",
+        );
+        r.get_mut().write_all(b"\x1b[<81;1;10M").unwrap();
+        assert_line(
+            &mut r,
+            "Mouse(MouseEvent { kind: Press, button: Some(WheelDown), \
+             modifiers: Modifiers(CTRL), row: 9, col: 0 }): \
+             [27, 91, 60, 56, 49, 59, 49, 59, 49, 48, 77]",
+        );
+        r.get_mut().write_all(b"\x1b[<39;80;24M").unwrap();
+        assert_line(
+            &mut r,
+            "Mouse(MouseEvent { kind: Move, button: None, \
+             modifiers: Modifiers(SHIFT), row: 23, col: 79 }): \
+             [27, 91, 60, 51, 57, 59, 56, 48, 59, 50, 52, 77]",
+        );
+        assert_no_more_lines(&mut r);
+
+        write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+        assert_line(&mut r, "Ctrl(99): [3]");
+        assert_cleanup(&mut r, b"\x1b[?1006l\x1b[?1002l");
+    });
+}
+
+#[test]
+fn test_escape_timeout() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    let mut run = fixture.build();
+    run_escape_timeout_test(&mut run);
+}
+
+#[test]
+fn test_escape_timeout_async() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    fixture.features("async");
+    let mut run = fixture.build();
+    run_escape_timeout_test(&mut run);
+}
+
+fn run_escape_timeout_test(fixture: &mut fixtures::BuiltFixture) {
+    fixture.run(&["--escape-timeout"], |pty| {
+        let mut r = std::io::BufReader::new(pty);
+
+        r.get_mut().write_all(b"\x1b").unwrap();
+        assert_no_more_lines(&mut r);
+        r.get_mut().write_all(b"[1;").unwrap();
+        assert_no_more_lines(&mut r);
+        r.get_mut().write_all(b"5A").unwrap();
+        assert_line(
+            &mut r,
+            "Modified(Up, Modifiers(CTRL)): [27, 91, 49, 59, 53, 65]",
+        );
+        assert_no_more_lines(&mut r);
+
+        r.get_mut().write_all(b"\x1b").unwrap();
+        assert_no_more_lines(&mut r);
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        assert_line(&mut r, "Escape: [27]");
+        assert_no_more_lines(&mut r);
+
+        r.get_mut().write_all(b"\x1bx").unwrap();
+        assert_line(&mut r, "Meta(120): [27, 120]");
+        assert_no_more_lines(&mut r);
+
+        write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+        assert_line(&mut r, "Ctrl(99): [3]");
+    });
+}
+
+#[test]
+fn test_read_timeout() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    let mut run = fixture.build();
+    run.run(&["--read-timeout", "--escape-timeout"], |pty| {
+        let mut r = std::io::BufReader::new(pty);
+
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert_line(&mut r, "timeout");
+        assert_no_more_lines(&mut r);
+
+        r.get_mut().write_all(b"a").unwrap();
+        assert_line(&mut r, "Char('a'): [97]");
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert_line(&mut r, "timeout");
+        assert_no_more_lines(&mut r);
+
+        // partial keys survive timing out
+        r.get_mut().write_all(b"\xe3\x81").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        assert_no_more_lines(&mut r);
+        r.get_mut().write_all(b"\x82").unwrap();
+        assert_line(&mut r, "Char('\u{3042}'): [227, 129, 130]");
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert_line(&mut r, "timeout");
+        assert_no_more_lines(&mut r);
+
+        r.get_mut().write_all(b"\x1b[1;").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        assert_no_more_lines(&mut r);
+        r.get_mut().write_all(b"5A").unwrap();
+        assert_line(
+            &mut r,
+            "Modified(Up, Modifiers(CTRL)): [27, 91, 49, 59, 53, 65]",
+        );
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert_line(&mut r, "timeout");
+        assert_no_more_lines(&mut r);
+
+        // the escape timeout is longer than the read timeout, but still
+        // expires
+        r.get_mut().write_all(b"\x1b").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        assert_no_more_lines(&mut r);
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        assert_line(&mut r, "Escape: [27]");
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert_line(&mut r, "timeout");
+        assert_no_more_lines(&mut r);
+
+        write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+        assert_line(&mut r, "Ctrl(99): [3]");
+    });
+}
+
+#[test]
+fn test_keymap() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    let mut run = fixture.build();
+    run_keymap_test(&mut run);
+}
+
+#[test]
+fn test_keymap_async() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    fixture.features("async");
+    let mut run = fixture.build();
+    run_keymap_test(&mut run);
+}
+
+fn run_keymap_test(fixture: &mut fixtures::BuiltFixture) {
+    fixture.run(&["--keymap"], |pty| {
+        let mut r = std::io::BufReader::new(pty);
+
+        r.get_mut().write_all(b"\x18").unwrap();
+        assert_no_more_lines(&mut r);
+        r.get_mut().write_all(b"\x13").unwrap();
+        assert_line(&mut r, "Action(\"save\")");
+        assert_no_more_lines(&mut r);
+
+        r.get_mut().write_all(b"\x18k").unwrap();
+        assert_line(&mut r, "Action(\"kill\")");
+        assert_no_more_lines(&mut r);
+
+        r.get_mut().write_all(b"a").unwrap();
+        assert_line(&mut r, "Unmatched([Char('a')])");
+        assert_no_more_lines(&mut r);
+
+        r.get_mut().write_all(b"\x18a").unwrap();
+        assert_line(&mut r, "Unmatched([Ctrl(120), Char('a')])");
+        assert_no_more_lines(&mut r);
+
+        r.get_mut().write_all(b"\x18").unwrap();
+        assert_no_more_lines(&mut r);
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        assert_line(&mut r, "Unmatched([Ctrl(120)])");
+        assert_no_more_lines(&mut r);
+
+        r.get_mut().write_all(b"gg").unwrap();
+        assert_line(&mut r, "Action(\"top\")");
+        assert_no_more_lines(&mut r);
+
+        r.get_mut().write_all(b"g").unwrap();
+        assert_no_more_lines(&mut r);
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        assert_line(&mut r, "Action(\"g\")");
+        assert_no_more_lines(&mut r);
+
+        write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+        assert_line(&mut r, "Unmatched([Ctrl(99)])");
+    });
+}
+
+#[test]
+fn test_stream_async() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    fixture.features("async");
+    let mut run = fixture.build();
+    run.run(&["--stream"], |pty| {
+        let mut r = std::io::BufReader::new(pty);
+
+        r.get_mut().write_all(b"a").unwrap();
+        assert_line(&mut r, "Char('a'): [97]");
+        assert_no_more_lines(&mut r);
+
+        r.get_mut().write_all(b"\x1b[A").unwrap();
+        assert_line(&mut r, "Up: [27, 91, 65]");
+        assert_no_more_lines(&mut r);
+
+        write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+        assert_line(&mut r, "Ctrl(99): [3]");
+    });
+}
+
+#[test]
+fn test_shutdown_async() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    fixture.features("async");
+    let mut run = fixture.build();
+    for args in [&["--shutdown"][..], &["--shutdown", "--tty"][..]] {
+        // the process exiting without any more input is what's being tested
+        run.run(args, |pty| {
+            let mut r = std::io::BufReader::new(pty);
+            assert_line(&mut r, "done");
+        });
+    }
+}
+
+#[test]
+fn test_focus_reporting() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    let mut run = fixture.build();
+    run_focus_reporting_test(&mut run);
+}
+
+#[test]
+fn test_focus_reporting_async() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    fixture.features("async");
+    let mut run = fixture.build();
+    run_focus_reporting_test(&mut run);
+}
+
+fn run_focus_reporting_test(fixture: &mut fixtures::BuiltFixture) {
+    fixture.run(&["--enable-focus-reporting"], |pty| {
+        assert_eq!(fixtures::read(pty), b"\x1b[?1004h");
+
+        let mut r = std::io::BufReader::new(pty);
+        r.get_mut().write_all(b"\x1b[O").unwrap();
+        assert_line(&mut r, "FocusLost: [27, 91, 79]");
+        r.get_mut().write_all(b"\x1b[I").unwrap();
+        assert_line(&mut r, "FocusGained: [27, 91, 73]");
+        assert_no_more_lines(&mut r);
+
+        write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+        assert_line(&mut r, "Ctrl(99): [3]");
+        assert_cleanup(&mut r, b"\x1b[?1004l");
+    });
+}
+
+#[test]
+fn test_query() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    let mut run = fixture.build();
+    run_query_test(&mut run);
+}
+
+#[test]
+fn test_query_async() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    fixture.features("async");
+    let mut run = fixture.build();
+    run_query_test(&mut run);
+}
+
+fn run_query_test(fixture: &mut fixtures::BuiltFixture) {
+    fixture.run(
+        &["--query-cursor-position", "--query-device-status"],
+        |pty| {
+            assert_eq!(fixtures::read(pty), b"\x1b[6n");
+            pty.write_all(b"a\x1b[5;10R").unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            pty.write_all(b"\x1b[Ab").unwrap();
+
+            let mut r = std::io::BufReader::new(pty);
+            assert_line(&mut r, "Some((4, 9))");
+            assert_cleanup(&mut r, b"\x1b[5n");
+            r.get_mut().write_all(b"\x1b[0n").unwrap();
+            assert_line(&mut r, "Some(true)");
+            assert_line(&mut r, "Char('a'): [97]");
+            assert_line(&mut r, "Up: [27, 91, 65]");
+            assert_line(&mut r, "Char('b'): [98]");
+            assert_no_more_lines(&mut r);
+
+            write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+            assert_line(&mut r, "Ctrl(99): [3]");
+        },
+    );
+
+    fixture.run(&["--query-cursor-position"], |pty| {
+        assert_eq!(fixtures::read(pty), b"\x1b[6n");
+        pty.write_all(b"a").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+
+        let mut r = std::io::BufReader::new(pty);
+        assert_line(&mut r, "None");
+        assert_line(&mut r, "Char('a'): [97]");
+        assert_no_more_lines(&mut r);
+
+        write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+        assert_line(&mut r, "Ctrl(99): [3]");
+    });
+}
+
+#[test]
+fn test_tty() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    let mut run = fixture.build();
+    run_tty_test(&mut run);
+}
+
+#[test]
+fn test_tty_async() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    fixture.features("async");
+    let mut run = fixture.build();
+    run_tty_test(&mut run);
+}
+
+fn run_tty_test(fixture: &mut fixtures::BuiltFixture) {
+    fixture.run(&["--tty", "--query-cursor-position"], |pty| {
+        assert_eq!(fixtures::read(pty), b"\x1b[6n");
+        pty.write_all(b"\x1b[2;3R").unwrap();
+
+        let mut r = std::io::BufReader::new(pty);
+        assert_line(&mut r, "Some((1, 2))");
+        write(r.get_mut(), textmode::Key::Up);
+        assert_line(&mut r, "Up: [27, 91, 65]");
+        assert_no_more_lines(&mut r);
+
+        write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+        assert_line(&mut r, "Ctrl(99): [3]");
+    });
+}
+
+#[test]
+fn test_terminfo_keys() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    let mut run = fixture.build();
+    run_terminfo_keys_test(&mut run);
+}
+
+#[test]
+fn test_terminfo_keys_async() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    fixture.features("async");
+    let mut run = fixture.build();
+    run_terminfo_keys_test(&mut run);
+}
+
+fn run_terminfo_keys_test(fixture: &mut fixtures::BuiltFixture) {
+    fixture.run(&["--terminfo-linux"], |pty| {
+        let mut r = std::io::BufReader::new(pty);
+
+        // sequences from the linux terminfo entry
+        r.get_mut().write_all(b"\x1b[[A").unwrap();
+        assert_line(&mut r, "F(1): [27, 79, 80]");
+        r.get_mut().write_all(b"\x1b[[E").unwrap();
+        assert_line(&mut r, "F(5): [27, 91, 49, 53, 126]");
+        r.get_mut().write_all(b"\x1b[1~").unwrap();
+        assert_line(&mut r, "Home: [27, 91, 72]");
+        r.get_mut().write_all(b"\x1b[4~").unwrap();
+        assert_line(&mut r, "End: [27, 91, 70]");
+        r.get_mut().write_all(b"\x1b\t").unwrap();
+        assert_line(&mut r, "BackTab: [27, 91, 90]");
+
+        // sequences which fall back to the built-in parsing
+        r.get_mut().write_all(b"\x1b[15~").unwrap();
+        assert_line(&mut r, "F(5): [27, 91, 49, 53, 126]");
+        r.get_mut().write_all(b"\x1b[Z").unwrap();
+        assert_line(&mut r, "BackTab: [27, 91, 90]");
+        r.get_mut().write_all(b"\x1b[1;5A").unwrap();
+        assert_line(
+            &mut r,
+            "Modified(Up, Modifiers(CTRL)): [27, 91, 49, 59, 53, 65]",
+        );
+        assert_no_more_lines(&mut r);
+
+        write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+        assert_line(&mut r, "Ctrl(99): [3]");
+    });
+}
+
+#[test]
+fn test_bracketed_paste() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    let mut run = fixture.build();
+    run_bracketed_paste_test(&mut run);
+}
+
+#[test]
+fn test_bracketed_paste_async() {
+    let mut fixture = fixtures::Fixture::new("input");
+    fixture.screenguard(false);
+    fixture.features("async");
+    let mut run = fixture.build();
+    run_bracketed_paste_test(&mut run);
+}
+
+fn run_bracketed_paste_test(fixture: &mut fixtures::BuiltFixture) {
+    fixture.run(&["--enable-bracketed-paste"], |pty| {
+        assert_output(
+            pty,
+            b"\x1b7\x1b[?47h\x1b[2J\x1b[H\x1b[?25h\x1b[?2004h",
+        );
+
+        let mut r = std::io::BufReader::new(pty);
+        r.get_mut().write_all(b"\x1b[200~foo\r").unwrap();
+        assert_no_more_lines(&mut r);
+        r.get_mut()
+            .write_all("\x1b[201😀\x1b[201~".as_bytes())
+            .unwrap();
+        assert_line(
+            &mut r,
+            "Paste(\"foo\\r\\u{1b}[201😀\"): [27, 91, 50, 48, 48, 126, \
+             102, 111, 111, 13, 27, 91, 50, 48, 49, 240, 159, 152, 128, \
+             27, 91, 50, 48, 49, 126]",
+        );
+        assert_no_more_lines(&mut r);
+
+        write(r.get_mut(), textmode::Key::Ctrl(b'c'));
+        assert_line(&mut r, "Ctrl(99): [3]");
+        assert_cleanup(&mut r, b"\x1b[?2004l\x1b[?47l\x1b8\x1b[?25h");
+    });
+}
+
 // the structure of the if statements here are easier to interpret uncollapsed
 #[allow(clippy::collapsible_else_if)]
 fn run_input_test(
@@ -122,6 +672,93 @@ fn run_input_test(
         }
         assert_no_more_lines(&mut r);
 
+        write(
+            r.get_mut(),
+            textmode::Key::Modified(
+                Box::new(textmode::Key::Up),
+                textmode::Modifiers::CTRL,
+            ),
+        );
+        if special_keys {
+            assert_line(
+                &mut r,
+                "Modified(Up, Modifiers(CTRL)): [27, 91, 49, 59, 53, 65]",
+            );
+        } else {
+            assert_unparsed_escape(&mut r, b"[1;5A", utf8, meta, single);
+        }
+        assert_no_more_lines(&mut r);
+
+        write(
+            r.get_mut(),
+            textmode::Key::Modified(
+                Box::new(textmode::Key::F(5)),
+                textmode::Modifiers::SHIFT | textmode::Modifiers::ALT,
+            ),
+        );
+        if special_keys {
+            assert_line(
+                &mut r,
+                "Modified(F(5), Modifiers(SHIFT | ALT)): \
+                 [27, 91, 49, 53, 59, 52, 126]",
+            );
+        } else {
+            assert_unparsed_escape(&mut r, b"[15;4~", utf8, meta, single);
+        }
+        assert_no_more_lines(&mut r);
+
+        write(
+            r.get_mut(),
+            textmode::Key::Event(Box::new(textmode::KeyEvent {
+                key: textmode::Key::Char('i'),
+                modifiers: textmode::Modifiers::CTRL,
+                kind: textmode::KeyEventKind::Press,
+            })),
+        );
+        if special_keys {
+            assert_line(
+                &mut r,
+                "Event(KeyEvent { key: Char('i'), \
+                 modifiers: Modifiers(CTRL), kind: Press }): \
+                 [27, 91, 49, 48, 53, 59, 53, 58, 49, 117]",
+            );
+        } else {
+            assert_unparsed_escape(&mut r, b"[105;5:1u", utf8, meta, single);
+        }
+        assert_no_more_lines(&mut r);
+
+        write(
+            r.get_mut(),
+            textmode::Key::Event(Box::new(textmode::KeyEvent {
+                key: textmode::Key::Left,
+                modifiers: textmode::Modifiers::NONE,
+                kind: textmode::KeyEventKind::Release,
+            })),
+        );
+        if special_keys {
+            assert_line(
+                &mut r,
+                "Event(KeyEvent { key: Left, \
+                 modifiers: Modifiers(NONE), kind: Release }): \
+                 [27, 91, 49, 59, 49, 58, 51, 68]",
+            );
+        } else {
+            assert_unparsed_escape(&mut r, b"[1;1:3D", utf8, meta, single);
+        }
+        assert_no_more_lines(&mut r);
+
+        // without special key parsing, this is just the same as any other
+        // unparsed escape sequences
+        if special_keys {
+            write(r.get_mut(), textmode::Key::Paste("foo bar".to_string()));
+            assert_line(
+                &mut r,
+                "Paste(\"foo bar\"): [27, 91, 50, 48, 48, 126, 102, 111, \
+                 111, 32, 98, 97, 114, 27, 91, 50, 48, 49, 126]",
+            );
+            assert_no_more_lines(&mut r);
+        }
+
         write(r.get_mut(), textmode::Key::Meta(b'c'));
         if meta {
             assert_line(&mut r, "Meta(99): [27, 99]");
@@ -310,6 +947,47 @@ fn run_input_test(
     });
 }
 
+// the expected output when special key parsing is disabled and an escape
+// sequence with the given trailing bytes (all printable ascii) is received
+#[allow(clippy::collapsible_else_if)]
+#[track_caller]
+fn assert_unparsed_escape(
+    f: &mut std::io::BufReader<&mut pty_process::blocking::Pty>,
+    rest: &[u8],
+    utf8: bool,
+    meta: bool,
+    single: bool,
+) {
+    if single {
+        assert_line(f, "Byte(27): [27]");
+        for &c in rest {
+            if utf8 {
+                assert_line(
+                    f,
+                    &format!("Char({:?}): [{}]", char::from(c), c),
+                );
+            } else {
+                assert_line(f, &format!("Byte({}): [{}]", c, c));
+            }
+        }
+    } else {
+        if utf8 {
+            assert_line(f, "Bytes([27]): [27]");
+            let s = std::str::from_utf8(rest).unwrap();
+            assert_line(f, &format!("String({:?}): {:?}", s, rest));
+        } else {
+            if meta {
+                assert_line(f, "Bytes([27]): [27]");
+                assert_line(f, &format!("Bytes({:?}): {:?}", rest, rest));
+            } else {
+                let mut bytes = vec![27];
+                bytes.extend(rest);
+                assert_line(f, &format!("Bytes({:?}): {:?}", bytes, bytes));
+            }
+        }
+    }
+}
+
 #[track_caller]
 fn write(f: &mut pty_process::blocking::Pty, key: textmode::Key) {
     f.write_all(&key.into_bytes()).unwrap();
@@ -330,6 +1008,33 @@ fn assert_line(
     assert_eq!(read(f), format!("{}\r\n", expected));
 }
 
+// the expected output may be written in several chunks
+#[track_caller]
+fn assert_output(f: &mut pty_process::blocking::Pty, expected: &[u8]) {
+    let mut buf = vec![];
+    while buf.len() < expected.len() && fixtures::read_ready(&f) {
+        buf.extend(fixtures::read(f));
+    }
+    assert_eq!(buf, expected);
+}
+
+// escape sequences written without a trailing newline (like the cleanup
+// sequence) may have been read along with the last line
+#[track_caller]
+fn assert_cleanup(
+    f: &mut std::io::BufReader<&mut pty_process::blocking::Pty>,
+    expected: &[u8],
+) {
+    let buf = if f.buffer().is_empty() {
+        fixtures::read(f.get_mut())
+    } else {
+        let buf = f.buffer().to_vec();
+        std::io::BufRead::consume(f, buf.len());
+        buf
+    };
+    assert_eq!(buf, expected);
+}
+
 #[track_caller]
 fn assert_no_more_lines(
     f: &mut std::io::BufReader<&mut pty_process::blocking::Pty>,
@@ -0,0 +1,152 @@
+use textmode::{Key, Modifiers};
+
+#[test]
+fn test_display() {
+    for (key, s) in [
+        (Key::Char('a'), "a"),
+        (Key::Char('A'), "A"),
+        (Key::Char('-'), "-"),
+        (Key::Char(' '), "Space"),
+        (Key::Char('\\'), "\\\\"),
+        (Key::Char('\u{7f}'), "\\u{7f}"),
+        (Key::Char('é'), "é"),
+        (Key::Byte(b'a'), "\\x61"),
+        (Key::Ctrl(b'a'), "C-a"),
+        (Key::Ctrl(b'i'), "Tab"),
+        (Key::Ctrl(b'm'), "Enter"),
+        (Key::Meta(b'x'), "M-x"),
+        (Key::Meta(b' '), "M-Space"),
+        (Key::Meta(0x80), "M-\\x80"),
+        (Key::Escape, "Esc"),
+        (Key::Backspace, "Backspace"),
+        (Key::PageDown, "PageDown"),
+        (Key::F(5), "F5"),
+        (Key::Modified(Box::new(Key::Up), Modifiers::SHIFT), "S-Up"),
+        (
+            Key::Modified(
+                Box::new(Key::F(12)),
+                Modifiers::CTRL | Modifiers::ALT,
+            ),
+            "C-M-F12",
+        ),
+        (
+            Key::String("foo \"bar\"".to_string()),
+            "\"foo \\\"bar\\\"\"",
+        ),
+        (Key::Bytes(b"a\x1b\xff".to_vec()), "b\"a\\x1b\\xff\""),
+        (Key::Paste("hi\n".to_string()), "Paste(\"hi\\u{a}\")"),
+    ] {
+        assert_eq!(key.to_string(), s);
+        assert_eq!(s.parse::<Key>().unwrap(), key);
+    }
+}
+
+#[test]
+fn test_parse_aliases() {
+    for (s, key) in [
+        ("Escape", Key::Escape),
+        ("Return", Key::Ctrl(b'm')),
+        ("C-i", Key::Ctrl(b'i')),
+        ("C-Space", Key::Ctrl(b' ')),
+        ("\\u{41}", Key::Char('A')),
+    ] {
+        assert_eq!(s.parse::<Key>().unwrap(), key);
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    for s in [
+        "",
+        "C-",
+        "ab",
+        "Foo",
+        "S-a",
+        "C-C-a",
+        "C-M-a",
+        "\"foo",
+        "\\q",
+        "F256",
+        "Mouse(Press)",
+    ] {
+        assert!(s.parse::<Key>().is_err(), "{s:?}");
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    let mut keys = vec![
+        Key::Up,
+        Key::Down,
+        Key::Right,
+        Key::Left,
+        Key::KeypadUp,
+        Key::KeypadDown,
+        Key::KeypadRight,
+        Key::KeypadLeft,
+        Key::Home,
+        Key::End,
+        Key::Insert,
+        Key::Delete,
+        Key::PageUp,
+        Key::BackTab,
+        Key::FocusGained,
+        Key::FocusLost,
+        Key::String(String::new()),
+        Key::Bytes(vec![]),
+        Key::Event(Box::new(textmode::KeyEvent {
+            key: Key::Char('a'),
+            modifiers: Modifiers::CTRL | Modifiers::SHIFT,
+            kind: textmode::KeyEventKind::Release,
+        })),
+        Key::Event(Box::new(textmode::KeyEvent {
+            key: Key::Ctrl(b'h'),
+            modifiers: Modifiers::NONE,
+            kind: textmode::KeyEventKind::Press,
+        })),
+        Key::Event(Box::new(textmode::KeyEvent {
+            key: Key::Char(','),
+            modifiers: Modifiers::SUPER,
+            kind: textmode::KeyEventKind::Repeat,
+        })),
+        Key::Mouse(textmode::MouseEvent {
+            kind: textmode::MouseEventKind::Press,
+            button: Some(textmode::MouseButton::Left),
+            modifiers: Modifiers::CTRL,
+            row: 4,
+            col: 10,
+        }),
+        Key::Mouse(textmode::MouseEvent {
+            kind: textmode::MouseEventKind::Move,
+            button: None,
+            modifiers: Modifiers::NONE,
+            row: 0,
+            col: 0,
+        }),
+        Key::Mouse(textmode::MouseEvent {
+            kind: textmode::MouseEventKind::Release,
+            button: Some(textmode::MouseButton::Other(9)),
+            modifiers: Modifiers::SHIFT | Modifiers::ALT,
+            row: 1,
+            col: 2,
+        }),
+    ];
+    for c in 0..=255 {
+        keys.push(Key::Byte(c));
+        keys.push(Key::Ctrl(c));
+        keys.push(Key::Meta(c));
+        keys.push(Key::Char(char::from(c)));
+    }
+    for n in 0..=63 {
+        keys.push(Key::F(n));
+        keys.push(Key::Modified(
+            Box::new(Key::F(n)),
+            Modifiers::SHIFT | Modifiers::SUPER | Modifiers::HYPER,
+        ));
+    }
+
+    for key in keys {
+        let s = key.to_string();
+        assert_eq!(s.parse::<Key>().as_ref(), Ok(&key), "{s:?}");
+    }
+}
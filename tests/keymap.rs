@@ -0,0 +1,88 @@
+use textmode::{Key, Keymap, KeymapEvent};
+
+fn keymap() -> Keymap<&'static str> {
+    let mut keymap = Keymap::new();
+    keymap.bind([Key::Char('a')], "a");
+    keymap.bind([Key::Char('a'), Key::Char('b')], "ab");
+    keymap.bind([Key::Char('c'), Key::Char('d')], "cd");
+    keymap
+}
+
+#[test]
+fn test_prefix_binding() {
+    let mut keymap = keymap();
+    assert_eq!(keymap.feed(Key::Char('a')), KeymapEvent::Pending);
+    assert_eq!(keymap.feed(Key::Char('b')), KeymapEvent::Action("ab"));
+    assert_eq!(keymap.next_event(), None);
+
+    // the shorter binding is used once the next key doesn't continue the
+    // longer one, and that key is matched again from the start
+    assert_eq!(keymap.feed(Key::Char('a')), KeymapEvent::Pending);
+    assert_eq!(keymap.feed(Key::Char('c')), KeymapEvent::Action("a"));
+    assert_eq!(keymap.next_event(), None);
+    assert_eq!(keymap.pending(), [Key::Char('c')]);
+    assert_eq!(keymap.feed(Key::Char('d')), KeymapEvent::Action("cd"));
+
+    assert_eq!(keymap.feed(Key::Char('a')), KeymapEvent::Pending);
+    assert_eq!(keymap.feed(Key::Char('x')), KeymapEvent::Action("a"));
+    assert_eq!(
+        keymap.next_event(),
+        Some(KeymapEvent::Unmatched(vec![Key::Char('x')]))
+    );
+    assert_eq!(keymap.next_event(), None);
+    assert!(!keymap.is_pending());
+
+    // keys which don't match anything are still all reported together
+    assert_eq!(keymap.feed(Key::Char('c')), KeymapEvent::Pending);
+    assert_eq!(
+        keymap.feed(Key::Char('a')),
+        KeymapEvent::Unmatched(vec![Key::Char('c'), Key::Char('a')])
+    );
+}
+
+#[test]
+fn test_prefix_binding_flush() {
+    let mut keymap = keymap();
+    assert_eq!(keymap.flush(), None);
+
+    assert_eq!(keymap.feed(Key::Char('a')), KeymapEvent::Pending);
+    assert_eq!(keymap.flush(), Some(KeymapEvent::Action("a")));
+    assert_eq!(keymap.next_event(), None);
+
+    assert_eq!(keymap.feed(Key::Char('a')), KeymapEvent::Pending);
+    assert_eq!(keymap.feed(Key::Char('c')), KeymapEvent::Action("a"));
+    assert_eq!(
+        keymap.flush(),
+        Some(KeymapEvent::Unmatched(vec![Key::Char('c')]))
+    );
+    assert_eq!(keymap.next_event(), None);
+}
+
+#[test]
+fn test_prefix_binding_several_events() {
+    let mut keymap = keymap();
+    keymap.bind([Key::Char('a'), Key::Char('b'), Key::Char('d')], "abd");
+    assert_eq!(keymap.feed(Key::Char('a')), KeymapEvent::Pending);
+    assert_eq!(keymap.feed(Key::Char('b')), KeymapEvent::Pending);
+    assert_eq!(keymap.feed(Key::Char('x')), KeymapEvent::Action("ab"));
+    assert_eq!(
+        keymap.next_event(),
+        Some(KeymapEvent::Unmatched(vec![Key::Char('x')]))
+    );
+
+    let mut keymap = Keymap::new();
+    keymap.bind([Key::Char('a')], "a");
+    keymap.bind([Key::Char('a'), Key::Char('b'), Key::Char('d')], "abd");
+    assert_eq!(keymap.feed(Key::Char('a')), KeymapEvent::Pending);
+    assert_eq!(keymap.feed(Key::Char('b')), KeymapEvent::Pending);
+    assert_eq!(keymap.feed(Key::Char('x')), KeymapEvent::Action("a"));
+    assert_eq!(
+        keymap.next_event(),
+        Some(KeymapEvent::Unmatched(vec![Key::Char('b')]))
+    );
+    assert_eq!(
+        keymap.next_event(),
+        Some(KeymapEvent::Unmatched(vec![Key::Char('x')]))
+    );
+    assert_eq!(keymap.next_event(), None);
+}
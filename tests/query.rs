@@ -0,0 +1,46 @@
+#[test]
+fn test_query_reader() {
+    // readers can't be waited on with a timeout, so this would otherwise
+    // block forever if the reply never arrived
+    let mut input =
+        textmode::blocking::Input::from_reader(&b"\x1b[5;10R"[..]);
+    let mut output = textmode::blocking::Output::from_writer(std::io::sink());
+    let err = input
+        .query_cursor_position(&mut output, std::time::Duration::from_secs(1))
+        .unwrap_err();
+    let textmode::Error::ReadStdin(err) = err else {
+        panic!("unexpected error {err}");
+    };
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_query_output() {
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+    let (reader, mut terminal_writer) = tokio::io::duplex(64);
+    let (mut terminal_reader, writer) = tokio::io::duplex(64);
+    let mut input = textmode::Input::from_reader(reader);
+    let mut output = textmode::Output::from_writer(writer);
+
+    // the request goes to the output, rather than to stdout
+    terminal_writer.write_all(b"a\x1b[5;10R").await.unwrap();
+    assert_eq!(
+        input
+            .query_cursor_position(
+                &mut output,
+                std::time::Duration::from_secs(1),
+            )
+            .await
+            .unwrap(),
+        Some((4, 9))
+    );
+    let mut request = [0; 4];
+    terminal_reader.read_exact(&mut request).await.unwrap();
+    assert_eq!(&request, b"\x1b[6n");
+    assert_eq!(
+        input.read_key().await.unwrap(),
+        Some(textmode::Key::Char('a'))
+    );
+}
@@ -0,0 +1,98 @@
+use textmode::Textmode as _;
+
+fn draw(tm: &mut impl textmode::Textmode) {
+    tm.set_size(10, 20);
+    tm.move_to(1, 2);
+    tm.set_fgcolor(textmode::color::RED);
+    tm.set_bold(true);
+    tm.write_str("foo");
+    tm.reset_attributes();
+    tm.move_to(3, 18);
+    tm.set_bgcolor(textmode::Color::Rgb(10, 20, 30));
+    tm.set_underline(true);
+    tm.write_str("ab");
+    tm.move_to(5, 0);
+    tm.write_str("\u{3042}x\u{3044}");
+    tm.move_to(6, 4);
+    tm.write(b"\x1b[2X");
+    tm.reset_attributes();
+    tm.set_italic(true);
+    tm.set_inverse(true);
+    tm.move_to(8, 7);
+    tm.hide_cursor(true);
+}
+
+#[test]
+fn test_restore() {
+    let mut tm = textmode::blocking::Output::from_writer(std::io::sink());
+    draw(&mut tm);
+    let snapshot = tm.snapshot();
+    assert_eq!(snapshot.size, (10, 20));
+    assert_eq!(snapshot.cursor_position, (8, 7));
+    assert!(snapshot.hide_cursor);
+    assert!(snapshot.attrs.italic && snapshot.attrs.inverse);
+    assert_eq!(snapshot.cells[1][2].contents, "f");
+    assert_eq!(snapshot.cells[1][2].attrs.fgcolor, textmode::color::RED);
+    assert!(snapshot.cells[5][0].wide);
+
+    let mut restored =
+        textmode::blocking::Output::from_writer(std::io::sink());
+    restored.write_str("garbage");
+    restored.restore_snapshot(&snapshot);
+    assert_eq!(restored.snapshot(), snapshot);
+    assert_eq!(
+        restored.screen().contents_formatted(),
+        tm.screen().contents_formatted()
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    let mut tm = textmode::blocking::Output::from_writer(std::io::sink());
+    draw(&mut tm);
+    let snapshot = tm.snapshot();
+    let json = serde_json::to_string(&snapshot).unwrap();
+    assert_eq!(
+        serde_json::from_str::<textmode::Snapshot>(&json).unwrap(),
+        snapshot
+    );
+
+    let keys = vec![
+        textmode::Key::Ctrl(b'x'),
+        textmode::Key::Char('"'),
+        textmode::Key::Modified(
+            Box::new(textmode::Key::Up),
+            textmode::Modifiers::SHIFT,
+        ),
+    ];
+    let json = serde_json::to_string(&keys).unwrap();
+    assert_eq!(json, r#"["C-x","\"","S-Up"]"#);
+    assert_eq!(
+        serde_json::from_str::<Vec<textmode::Key>>(&json).unwrap(),
+        keys
+    );
+    assert!(serde_json::from_str::<textmode::Key>(r#""C-""#).is_err());
+
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct Theme {
+        #[serde(with = "textmode::color::serde")]
+        fg: textmode::Color,
+        #[serde(with = "textmode::color::serde")]
+        bg: textmode::Color,
+        #[serde(with = "textmode::color::serde")]
+        border: textmode::Color,
+    }
+    let theme = Theme {
+        fg: textmode::Color::Default,
+        bg: textmode::color::BLUE,
+        border: textmode::Color::Rgb(255, 128, 0),
+    };
+    let json = serde_json::to_string(&theme).unwrap();
+    assert_eq!(json, r##"{"fg":"default","bg":4,"border":"#ff8000"}"##);
+    assert_eq!(serde_json::from_str::<Theme>(&json).unwrap(), theme);
+    assert!(serde_json::from_str::<Theme>(
+        r#"{"fg":"red","bg":4,"border":256}"#
+    )
+    .is_err());
+}
@@ -0,0 +1,26 @@
+// this replaces the test process's stdin, so it is kept on its own
+
+#[test]
+fn test_large_read() {
+    let (reader, writer) = nix::unistd::pipe().unwrap();
+    nix::unistd::dup2(std::os::fd::AsRawFd::as_raw_fd(&reader), 0).unwrap();
+    drop(reader);
+
+    // more than is read from stdin at once, so the rest has to still be
+    // visible as pending input on the fd
+    let mut bytes = vec![b'a'; 5000];
+    bytes.extend(b"\x1b[A");
+    nix::unistd::write(&writer, &bytes).unwrap();
+
+    let mut input = textmode::blocking::Input::new_without_raw();
+    let mut keys = vec![];
+    while let Some(key) = input.try_read_key().unwrap() {
+        keys.push(key);
+    }
+    assert_eq!(keys.last(), Some(&textmode::Key::Up));
+    let read: Vec<u8> = keys
+        .into_iter()
+        .flat_map(textmode::Key::into_bytes)
+        .collect();
+    assert_eq!(read, bytes);
+}
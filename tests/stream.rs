@@ -0,0 +1,59 @@
+#![cfg(feature = "async")]
+
+use futures::stream::StreamExt as _;
+
+#[tokio::test]
+async fn test_stream_eof() {
+    let input = textmode::Input::from_reader(&b"ab\x1b[A"[..]);
+    let keys: Vec<_> =
+        input.into_stream().map(Result::unwrap).collect().await;
+    assert_eq!(
+        keys,
+        vec![
+            textmode::Key::Char('a'),
+            textmode::Key::Char('b'),
+            textmode::Key::Up
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_stream_select() {
+    let (reader, mut writer) = tokio::io::duplex(64);
+    let mut stream = textmode::Input::from_reader(reader).into_stream();
+    let mut ticks =
+        tokio::time::interval(std::time::Duration::from_millis(10));
+
+    // abandoning the stream's future when the other branch wins shouldn't
+    // lose any input
+    let mut keys = vec![];
+    for i in 0..20 {
+        if i == 5 {
+            tokio::io::AsyncWriteExt::write_all(&mut writer, b"a")
+                .await
+                .unwrap();
+        }
+        if i == 10 {
+            tokio::io::AsyncWriteExt::write_all(&mut writer, b"\x1b[Bx")
+                .await
+                .unwrap();
+        }
+        tokio::select! {
+            key = stream.next() => keys.push(key.unwrap().unwrap()),
+            _ = ticks.tick() => {}
+        }
+    }
+    drop(writer);
+    while let Some(key) = stream.next().await {
+        keys.push(key.unwrap());
+    }
+    assert_eq!(
+        keys,
+        vec![
+            textmode::Key::Char('a'),
+            textmode::Key::Down,
+            textmode::Key::Char('x')
+        ]
+    );
+    assert!(stream.into_inner().is_some());
+}
@@ -0,0 +1,102 @@
+use textmode::Draw as _;
+use textmode::Textmode as _;
+
+mod common;
+
+#[test]
+fn test_set_style() {
+    let mut tm = common::output(4, 20);
+    assert_eq!(tm.current_style(), textmode::Style::default());
+
+    let styles = [
+        textmode::Style::new()
+            .fgcolor(textmode::color::RED)
+            .bold(true)
+            .italic(true)
+            .underline(true),
+        textmode::Style::new().fgcolor(textmode::color::GREEN),
+        textmode::Style::new()
+            .bgcolor(textmode::Color::Rgb(1, 2, 3))
+            .inverse(true),
+        textmode::Style::new()
+            .bgcolor(textmode::Color::Rgb(1, 2, 3))
+            .bold(true),
+        textmode::Style::new(),
+    ];
+    for (col, style) in (0..).zip(styles) {
+        tm.set_style(style);
+        assert_eq!(tm.current_style(), style);
+        tm.move_to(0, col);
+        tm.write_str("x");
+    }
+
+    for (col, style) in (0..).zip(styles) {
+        let cell = tm.screen().cell(0, col).unwrap();
+        assert_eq!(cell.fgcolor(), style.fgcolor);
+        assert_eq!(cell.bgcolor(), style.bgcolor);
+        assert_eq!(cell.bold(), style.bold);
+        assert_eq!(cell.italic(), style.italic);
+        assert_eq!(cell.underline(), style.underline);
+        assert_eq!(cell.inverse(), style.inverse);
+    }
+}
+
+#[test]
+fn test_set_style_individual_setters() {
+    let mut tm = common::output(4, 20);
+    tm.set_bold(true);
+    tm.set_bgcolor(textmode::color::BLUE);
+    assert_eq!(
+        tm.current_style(),
+        textmode::Style::new()
+            .bold(true)
+            .bgcolor(textmode::color::BLUE)
+    );
+    tm.set_style(textmode::Style::new().bold(true));
+    assert_eq!(tm.current_style(), textmode::Style::new().bold(true));
+}
+
+#[test]
+fn test_write_spans() {
+    let mut tm = common::output(4, 20);
+    let prev = textmode::Style::new().underline(true);
+    tm.set_style(prev);
+
+    let red = textmode::Style::new().fgcolor(textmode::color::RED);
+    tm.write_spans(&[
+        textmode::Span::new("foo", red),
+        "bar".into(),
+        textmode::Span::new(String::from("baz"), red.bold(true)),
+    ]);
+    assert_eq!(tm.current_style(), prev);
+    tm.write_styled("!", textmode::Style::new().italic(true));
+    assert_eq!(tm.current_style(), prev);
+    tm.write_str("?");
+
+    assert_eq!(tm.screen().contents(), "foobarbaz!?");
+    let cell = |col| tm.screen().cell(0, col).unwrap();
+    assert_eq!(cell(0).fgcolor(), textmode::color::RED);
+    assert!(!cell(0).underline());
+    assert_eq!(cell(3).fgcolor(), textmode::Color::Default);
+    assert!(!cell(3).bold() && !cell(3).underline());
+    assert!(cell(6).bold());
+    assert!(cell(9).italic() && !cell(9).underline());
+    assert!(cell(10).underline());
+
+    assert_eq!(textmode::Span::from("\u{3042}x").width(), 3);
+}
+
+#[test]
+fn test_view_spans() {
+    let mut tm = common::output(4, 20);
+    let mut view = tm.view(1, 2, 1, 5);
+    let red = textmode::Style::new().fgcolor(textmode::color::RED);
+    view.write_spans(&[
+        textmode::Span::new("foo", red),
+        textmode::Span::new("barbaz", red.inverse(true)),
+    ]);
+    assert_eq!(view.current_style(), textmode::Style::new());
+    assert_eq!(tm.screen().rows(0, 20).nth(1).unwrap(), "  fooba");
+    assert!(tm.screen().cell(1, 6).unwrap().inverse());
+    assert!(tm.screen().cell(1, 7).unwrap().contents().is_empty());
+}
@@ -0,0 +1,119 @@
+use textmode::Draw as _;
+use textmode::Textmode as _;
+
+#[test]
+fn test_width() {
+    assert_eq!(textmode::text::width("foo"), 3);
+    assert_eq!(textmode::text::width("\u{65e5}\u{672c}\u{8a9e}"), 6);
+    assert_eq!(textmode::text::width("e\u{301}te\u{301}"), 3);
+    assert_eq!(textmode::text::width("\u{1f600}!"), 3);
+    assert_eq!(textmode::text::width("a\tb\x1b"), 2);
+    assert_eq!(textmode::text::width(""), 0);
+}
+
+#[test]
+fn test_truncate() {
+    let ellipsis = textmode::text::ELLIPSIS;
+    assert_eq!(textmode::text::truncate("foobar", 6, ellipsis), "foobar");
+    assert_eq!(textmode::text::truncate("foobar", 5, ellipsis), "foob…");
+    assert_eq!(textmode::text::truncate("foobar", 5, "..."), "fo...");
+    assert_eq!(textmode::text::truncate("foobar", 2, "..."), "fo");
+    assert_eq!(textmode::text::truncate("foobar", 0, ellipsis), "");
+
+    // wide characters which don't fit are dropped entirely
+    assert_eq!(
+        textmode::text::truncate("\u{65e5}\u{672c}\u{8a9e}", 5, ellipsis),
+        "\u{65e5}\u{672c}…"
+    );
+    assert_eq!(
+        textmode::text::truncate("\u{65e5}\u{672c}\u{8a9e}", 4, ellipsis),
+        "\u{65e5}…"
+    );
+
+    // combining characters stay with the character they modify
+    assert_eq!(
+        textmode::text::truncate("cafe\u{301}s", 4, ""),
+        "cafe\u{301}"
+    );
+    assert_eq!(
+        textmode::text::truncate("cafe\u{301}s", 4, ellipsis),
+        "caf…"
+    );
+}
+
+#[test]
+fn test_align() {
+    use textmode::text::Align;
+
+    assert_eq!(textmode::text::align("foo", 7, Align::Left), "foo    ");
+    assert_eq!(textmode::text::align("foo", 7, Align::Center), "  foo  ");
+    assert_eq!(textmode::text::align("foo", 8, Align::Center), "  foo   ");
+    assert_eq!(textmode::text::align("foo", 7, Align::Right), "    foo");
+    assert_eq!(
+        textmode::text::align("\u{65e5}\u{672c}", 6, Align::Center),
+        " \u{65e5}\u{672c} "
+    );
+    assert_eq!(
+        textmode::text::align("\u{65e5}\u{672c}\u{8a9e}", 4, Align::Right),
+        " \u{65e5}…"
+    );
+    assert_eq!(textmode::text::align("foobar", 4, Align::Left), "foo…");
+}
+
+#[test]
+fn test_wrap() {
+    assert_eq!(
+        textmode::text::wrap("the quick brown fox jumps", 10),
+        ["the quick", "brown fox", "jumps"]
+    );
+    assert_eq!(
+        textmode::text::wrap("  lots   of\tspace  ", 20),
+        ["lots of space"]
+    );
+    assert_eq!(
+        textmode::text::wrap("first\n\nsecond line", 6),
+        ["first", "", "second", "line"]
+    );
+    assert_eq!(
+        textmode::text::wrap("a supercalifragilistic word", 8),
+        ["a", "supercal", "ifragili", "stic", "word"]
+    );
+    assert_eq!(
+        textmode::text::wrap("\u{65e5}\u{672c}\u{8a9e}\u{65e5}\u{672c}", 5),
+        ["\u{65e5}\u{672c}", "\u{8a9e}\u{65e5}", "\u{672c}"]
+    );
+    assert_eq!(
+        textmode::text::wrap("e\u{301}e\u{301}e\u{301} x", 2),
+        ["e\u{301}e\u{301}", "e\u{301}", "x"]
+    );
+    assert!(textmode::text::wrap("", 10).is_empty());
+}
+
+#[test]
+fn test_write() {
+    let mut tm = textmode::blocking::Output::from_writer(std::io::sink());
+    tm.set_size(6, 12);
+
+    tm.move_to(0, 1);
+    tm.write_truncated("\u{65e5}\u{672c}\u{8a9e}", 5);
+    tm.write_str("|");
+
+    tm.move_to(1, 0);
+    tm.write_aligned("mid", 12, textmode::text::Align::Center);
+    assert_eq!(tm.screen().cursor_position(), (1, 12));
+
+    tm.move_to(2, 2);
+    assert_eq!(tm.write_wrapped("the quick brown fox", 9), 2);
+    assert_eq!(tm.screen().cursor_position(), (3, 11));
+
+    assert_eq!(
+        tm.screen().rows(0, 12).collect::<Vec<_>>()[..5],
+        [
+            " \u{65e5}\u{672c}…|",
+            "    mid     ",
+            "  the quick",
+            "  brown fox",
+            "",
+        ]
+    );
+}
@@ -0,0 +1,149 @@
+use textmode::Draw as _;
+use textmode::Textmode as _;
+
+mod common;
+
+#[test]
+fn test_view() {
+    let mut tm = common::output(6, 20);
+    tm.write_str("xxxxxxxxxxxxxxxxxxxx");
+
+    let mut view = tm.view(1, 2, 3, 5);
+    assert_eq!(view.size(), (3, 5));
+    assert_eq!(view.origin(), (1, 2));
+    view.write_str("foo");
+    view.write_str("barbaz");
+    assert_eq!(view.cursor_position(), (0, 9));
+    view.move_to(2, 3);
+    view.write_str("quux");
+    view.move_to(3, 0);
+    view.write_str("hidden");
+    view.move_to(1, 1);
+    assert_eq!(tm.screen().cursor_position(), (2, 3));
+
+    assert_eq!(
+        common::rows(tm.screen())[..5],
+        ["xxxxxxxxxxxxxxxxxxxx", "  fooba", "", "     qu", "",]
+    );
+}
+
+#[test]
+fn test_view_wide() {
+    let mut tm = common::output(6, 20);
+    tm.write_str("xxxxxxxxxxxxxxxxxxxx");
+    tm.move_to(1, 0);
+    tm.write_str("xxxxxxxxxxxxxxxxxxxx");
+
+    let mut view = tm.view(0, 2, 2, 5);
+    view.set_fgcolor(textmode::color::RED);
+    view.write_str("\u{3042}\u{3044}\u{3046}");
+    view.move_to(1, 0);
+    view.write_str("a\u{3042}\u{3044}b");
+
+    // the third character would cover the view's last column and the
+    // column after it, so only its first half is blanked
+    assert_eq!(
+        common::rows(tm.screen())[0],
+        "xx\u{3042}\u{3044} xxxxxxxxxxxxx"
+    );
+    assert_eq!(
+        common::rows(tm.screen())[1],
+        "xxa\u{3042}\u{3044}xxxxxxxxxxxxx"
+    );
+    assert_eq!(
+        tm.screen().cell(0, 6).unwrap().fgcolor(),
+        textmode::color::RED
+    );
+    assert_eq!(tm.screen().cell(0, 7).unwrap().contents(), "x");
+}
+
+#[test]
+fn test_view_clear() {
+    let mut tm = common::output(6, 20);
+    for row in 0..6 {
+        tm.move_to(row, 0);
+        tm.write_str("xxxxxxxxxxxxxxxxxxxx");
+    }
+
+    let mut view = tm.view(1, 3, 3, 4);
+    view.move_to(1, 2);
+    view.clear_line();
+    assert_eq!(view.cursor_position(), (1, 2));
+    assert_eq!(common::rows(tm.screen())[2], "xxxxx  xxxxxxxxxxxxx");
+
+    let mut view = tm.view(1, 3, 3, 4);
+    view.set_bgcolor(textmode::color::BLUE);
+    view.clear();
+    assert_eq!(
+        common::rows(tm.screen()),
+        [
+            "xxxxxxxxxxxxxxxxxxxx",
+            "xxx    xxxxxxxxxxxxx",
+            "xxx    xxxxxxxxxxxxx",
+            "xxx    xxxxxxxxxxxxx",
+            "xxxxxxxxxxxxxxxxxxxx",
+            "xxxxxxxxxxxxxxxxxxxx",
+        ]
+    );
+    assert_eq!(
+        tm.screen().cell(3, 6).unwrap().bgcolor(),
+        textmode::color::BLUE
+    );
+    assert_eq!(
+        tm.screen().cell(3, 7).unwrap().bgcolor(),
+        textmode::Color::Default
+    );
+}
+
+#[test]
+fn test_view_clipped_to_screen() {
+    let mut tm = common::output(6, 20);
+    let mut view = tm.view(4, 17, 10, 10);
+    assert_eq!(view.size(), (2, 3));
+    view.write_str("abcdef");
+    view.move_to(1, 0);
+    view.write_str("ghijkl");
+
+    let mut inner = view.view(1, 1, 5, 5);
+    assert_eq!(inner.origin(), (5, 18));
+    assert_eq!(inner.size(), (1, 2));
+    inner.write_str("XYZ");
+
+    assert_eq!(common::rows(tm.screen())[4], "                 abc");
+    assert_eq!(common::rows(tm.screen())[5], "                 gXY");
+
+    // the screen doesn't scroll when the bottom right corner is drawn to
+    assert_eq!(common::rows(tm.screen())[0], "");
+}
+
+// the same drawing code works on both outputs and views
+fn draw_label(d: &mut impl textmode::Draw, text: &str) {
+    d.draw_box(0, 0, 4, 8, textmode::BorderStyle::Single);
+    d.fill_rect(1, 1, 2, 6, '.');
+    d.write_wrapped(text, 6);
+}
+
+#[test]
+fn test_view_text() {
+    let mut tm = common::output(6, 20);
+    tm.move_to(1, 1);
+    draw_label(&mut tm, "ab cd");
+    let mut view = tm.view(0, 9, 4, 6);
+    view.move_to(1, 1);
+    draw_label(&mut view, "ab cdefgh");
+    assert_eq!(view.cursor_position(), (2, 7));
+    view.move_to(3, 1);
+    view.write_truncated("truncated", 4);
+    view.move_to(0, 1);
+    view.write_aligned("mid", 4, textmode::text::Align::Center);
+
+    assert_eq!(
+        common::rows(tm.screen())[..4],
+        [
+            "┌──────┐ ┌mid ─",
+            "│ab cd.│ │ab...",
+            "│......│ │cdefg",
+            "└──────┘ └tru…─",
+        ]
+    );
+}